@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * listed in the order they're ranked unless a user says otherwise
+ */
+export type ActivityKind = "streaming" | "gaming" | "watching" | "voice" | "listening" | "coding" | "custom" | "status";
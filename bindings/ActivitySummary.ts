@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Activity } from "./Activity";
+
+export type ActivitySummary = { 
+/**
+ * a single line describing the most important thing they're doing
+ */
+summary: string | null, 
+/**
+ * everything they're doing, most important first
+ */
+signals: Array<Activity>, };
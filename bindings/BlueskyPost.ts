@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BlueskyImage } from "./BlueskyImage";
+
+export type BlueskyPost = { url: string, created_at: string, text: string, images: Array<BlueskyImage>, replies: bigint, reposts: bigint, likes: bigint, };
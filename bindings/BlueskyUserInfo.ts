@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BlueskyPost } from "./BlueskyPost";
+
+export type BlueskyUserInfo = { handle: string, did: string, display_name: string | null, description: string | null, avatar: string | null, banner: string | null, followers: bigint, follows: bigint, post_count: bigint, posts: Array<BlueskyPost>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type CustomStatus = { text: string | null, emoji: string | null, url: string | null, 
+/**
+ * whatever else the source wants to pass along
+ */
+data: JsonValue | null, updated_at: string, expires_at: string, };
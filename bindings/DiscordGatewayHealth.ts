@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiscordGatewayState } from "./DiscordGatewayState";
+
+export type DiscordGatewayHealth = { state: DiscordGatewayState, latency_ms: bigint | null, last_event: string | null, disconnected_since: string | null, restarts: number, };
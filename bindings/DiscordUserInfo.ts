@@ -1,5 +1,10 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { DiscordCustomStatus } from "./DiscordCustomStatus";
 import type { DiscordOnlineStatus } from "./DiscordOnlineStatus";
+import type { DiscordVoiceChannel } from "./DiscordVoiceChannel";
 
-export type DiscordUserInfo = { display_name: string, status: DiscordOnlineStatus, client_status: DiscordOnlineStatus | null, custom_status: DiscordCustomStatus | null, };
+export type DiscordUserInfo = { display_name: string, status: DiscordOnlineStatus, client_status: DiscordOnlineStatus | null, custom_status: DiscordCustomStatus | null, voice: DiscordVoiceChannel | null, 
+/**
+ * set when the gateway has been down long enough that this info may be outdated
+ */
+stale: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type FediversePost = { url: string | null, created_at: string, 
+/**
+ * html, as rendered by the instance
+ */
+content: string, content_warning: string | null, media: Array<string>, replies: bigint, boosts: bigint, favourites: bigint, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FediversePost } from "./FediversePost";
+
+export type FediverseUserInfo = { handle: string, display_name: string, url: string, avatar: string, 
+/**
+ * html, as rendered by the instance
+ */
+bio: string, followers: bigint, following: bigint, posts: Array<FediversePost>, };
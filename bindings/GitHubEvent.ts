@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GitHubEvent = { "Push": { repo: string, created_at: string, branch: string, commits: bigint | null, } } | { "PullRequest": { repo: string, created_at: string, action: string, number: bigint, title: string, url: string, } } | { "Release": { repo: string, created_at: string, tag: string, name: string | null, url: string, } };
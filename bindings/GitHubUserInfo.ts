@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GitHubEvent } from "./GitHubEvent";
+import type { GitHubStatus } from "./GitHubStatus";
+
+export type GitHubUserInfo = { username: string, status: GitHubStatus | null, 
+/**
+ * over the last year, only available with a token
+ */
+contributions: bigint | null, events: Array<GitHubEvent>, };
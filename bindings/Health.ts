@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiscordGatewayHealth } from "./DiscordGatewayHealth";
+import type { LastFmHealth } from "./LastFmHealth";
+
+export type Health = { discord: DiscordGatewayHealth | null, last_fm: LastFmHealth | null, };
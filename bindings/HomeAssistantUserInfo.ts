@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HomeAssistantActivity } from "./HomeAssistantActivity";
+
+export type HomeAssistantUserInfo = { 
+/**
+ * phone battery percentage
+ */
+battery: number | null, charging: boolean | null, 
+/**
+ * whether a focus or do not disturb mode is on
+ */
+focus: boolean | null, activity: Array<HomeAssistantActivity>, };
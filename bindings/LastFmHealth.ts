@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LastFmUserHealth } from "./LastFmUserHealth";
+
+export type LastFmHealth = { rate_limited_until: string | null, users: { [key in string]?: LastFmUserHealth }, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LastFmTopAlbum } from "./LastFmTopAlbum";
+import type { LastFmTopArtist } from "./LastFmTopArtist";
+import type { LastFmTopTrack } from "./LastFmTopTrack";
+
+export type LastFmTopList = { artists: Array<LastFmTopArtist>, tracks: Array<LastFmTopTrack>, albums: Array<LastFmTopAlbum>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LastFmTopList } from "./LastFmTopList";
+
+export type LastFmTopLists = { seven_days: LastFmTopList, one_month: LastFmTopList, overall: LastFmTopList, };
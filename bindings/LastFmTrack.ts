@@ -2,4 +2,12 @@
 import type { LastFmArtist } from "./LastFmArtist";
 import type { LastFmImageSet } from "./LastFmImageSet";
 
-export type LastFmTrack = { artist: LastFmArtist, name: string, image: LastFmImageSet, album: string, url: string, start_time: string, };
+export type LastFmTrack = { artist: LastFmArtist, name: string, image: LastFmImageSet, album: string, url: string, start_time: string, loved: boolean | null, 
+/**
+ * the length of the track in milliseconds
+ */
+duration: bigint | null, 
+/**
+ * when the track should finish, assuming it's played through from `start_time`
+ */
+end_time: string | null, user_playcount: bigint | null, tags: Array<string>, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LastFmUserHealth = { last_success: string | null, last_error: string | null, last_error_time: string | null, consecutive_errors: number, };
@@ -1,4 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LastFmRecordedTrack } from "./LastFmRecordedTrack";
+import type { LastFmTopLists } from "./LastFmTopLists";
 import type { LastFmTrack } from "./LastFmTrack";
 
-export type LastFmUserInfo = { username: string, currently_playing: LastFmTrack | null, };
+export type LastFmUserInfo = { username: string, currently_playing: LastFmTrack | null, recent_tracks: Array<LastFmRecordedTrack>, scrobble_count: bigint | null, top: LastFmTopLists, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MediaKind } from "./MediaKind";
+import type { MediaServer } from "./MediaServer";
+
+export type MediaSession = { server: MediaServer, kind: MediaKind, title: string, series: string | null, season: number | null, episode: number | null, position_seconds: bigint | null, duration_seconds: bigint | null, 
+/**
+ * how far through the item they are, from 0 to 1
+ */
+progress: number | null, paused: boolean, poster_url: string | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SteamAchievement = { name: string, description: string | null, icon_url: string, unlocked_at: string, };
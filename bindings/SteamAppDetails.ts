@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SteamAppDetails = { header_image: string, short_description: string, genres: Array<string>, developers: Array<string>, release_date: string | null, };
@@ -1,3 +1,13 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteamAchievementProgress } from "./SteamAchievementProgress";
+import type { SteamAppDetails } from "./SteamAppDetails";
 
-export type SteamGameInfo = { appid: bigint, name: string, info_url: string, };
+export type SteamGameInfo = { appid: bigint, name: string, info_url: string, 
+/**
+ * filled in once the store page has been fetched
+ */
+details: SteamAppDetails | null, 
+/**
+ * missing for games without achievements, or while they're still being fetched
+ */
+achievements: SteamAchievementProgress | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteamPlayedGame } from "./SteamPlayedGame";
+
+export type SteamLibrary = { game_count: bigint, 
+/**
+ * in minutes
+ */
+total_playtime: bigint, most_played: Array<SteamPlayedGame>, };
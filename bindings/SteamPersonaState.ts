@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SteamPersonaState = "offline" | "online" | "busy" | "away" | "snooze" | "looking_to_trade" | "looking_to_play";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * playtimes are in minutes, as steam reports them
+ */
+export type SteamPlayedGame = { appid: bigint, name: string, playtime_two_weeks: bigint | null, playtime_forever: bigint, icon_url: string | null, header_url: string, };
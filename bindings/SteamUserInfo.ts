@@ -1,4 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteamAvatar } from "./SteamAvatar";
 import type { SteamGameInfo } from "./SteamGameInfo";
+import type { SteamLibrary } from "./SteamLibrary";
+import type { SteamPersonaState } from "./SteamPersonaState";
+import type { SteamPlayedGame } from "./SteamPlayedGame";
 
-export type SteamUserInfo = { steam_id: string, persona_name: string, game: SteamGameInfo | null, };
+export type SteamUserInfo = { steam_id: string, persona_name: string, persona_state: SteamPersonaState, last_logoff: string | null, avatar: SteamAvatar, profile_url: string, game: SteamGameInfo | null, recently_played: Array<SteamPlayedGame>, library: SteamLibrary | null, };
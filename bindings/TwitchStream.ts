@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TwitchStream = { title: string, game: string, viewers: bigint, started_at: string, thumbnail_url: string, };
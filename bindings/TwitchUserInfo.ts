@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TwitchStream } from "./TwitchStream";
+
+export type TwitchUserInfo = { login: string, live: boolean, stream: TwitchStream | null, };
@@ -1,7 +1,25 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActivitySummary } from "./ActivitySummary";
+import type { BlueskyUserInfo } from "./BlueskyUserInfo";
+import type { CustomStatus } from "./CustomStatus";
 import type { DiscordUserInfo } from "./DiscordUserInfo";
+import type { FediverseUserInfo } from "./FediverseUserInfo";
+import type { GitHubUserInfo } from "./GitHubUserInfo";
+import type { HomeAssistantUserInfo } from "./HomeAssistantUserInfo";
+import type { JsonValue } from "./serde_json/JsonValue";
 import type { LastFmUserInfo } from "./LastFmUserInfo";
-import type { MinimalUser } from "./MinimalUser";
+import type { Location } from "./Location";
+import type { MediaSession } from "./MediaSession";
 import type { SteamUserInfo } from "./SteamUserInfo";
+import type { TwitchUserInfo } from "./TwitchUserInfo";
+import type { WakaTimeUserInfo } from "./WakaTimeUserInfo";
 
-export type User = { name: string, owners: Array<MinimalUser>, aliases: Array<string>, pronouns: Array<string>, time_zone: string, discord: DiscordUserInfo | null, last_fm: LastFmUserInfo | null, steam: SteamUserInfo | null, };
+export type User = { name: string, aliases: Array<string>, pronouns: Array<string>, time_zone: string, discord: DiscordUserInfo | null, last_fm: LastFmUserInfo | null, listenbrainz: LastFmUserInfo | null, steam: SteamUserInfo | null, location: Location | null, github: GitHubUserInfo | null, wakatime: WakaTimeUserInfo | null, watching: MediaSession | null, fediverse: FediverseUserInfo | null, bluesky: BlueskyUserInfo | null, twitch: TwitchUserInfo | null, home_assistant: HomeAssistantUserInfo | null, 
+/**
+ * pushed by the user's own scripts, keyed by source
+ */
+custom: { [key in string]?: CustomStatus }, 
+/**
+ * from the json sources in the config, keyed by source and then field
+ */
+sources: { [key in string]?: { [key in string]?: JsonValue } }, activity: ActivitySummary, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CodingStatus } from "./CodingStatus";
+
+export type WakaTimeUserInfo = { 
+/**
+ * only set while the user has sent a heartbeat recently
+ */
+coding: CodingStatus | null, today_seconds: bigint, today_text: string, };
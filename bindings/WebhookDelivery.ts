@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WebhookEvent } from "./WebhookEvent";
+
+export type WebhookDelivery = { webhook: string, event_id: string, event: WebhookEvent, user: string, attempt: number, attempted_at: string, 
+/**
+ * missing when the request never got a response
+ */
+status: number | null, error: string | null, will_retry: boolean, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type WebhookEvent = "went_online" | "went_offline" | "activity_started" | "activity_changed" | "activity_ended";
@@ -10,3 +10,46 @@ export type { DiscordUserInfo } from "./DiscordUserInfo.ts";
 export type { DiscordOnlineStatus } from "./DiscordOnlineStatus.ts";
 export type { DiscordCustomStatus } from "./DiscordCustomStatus.ts";
 export type { DiscordEmoji } from "./DiscordEmoji.ts";
+export type { Location } from "./Location.ts";
+export type { DiscordVoiceChannel } from "./DiscordVoiceChannel.ts";
+export type { DiscordGatewayHealth } from "./DiscordGatewayHealth.ts";
+export type { DiscordGatewayState } from "./DiscordGatewayState.ts";
+export type { Health } from "./Health.ts";
+export type { LastFmRecordedTrack } from "./LastFmRecordedTrack.ts";
+export type { LastFmTopAlbum } from "./LastFmTopAlbum.ts";
+export type { LastFmTopArtist } from "./LastFmTopArtist.ts";
+export type { LastFmTopList } from "./LastFmTopList.ts";
+export type { LastFmTopLists } from "./LastFmTopLists.ts";
+export type { LastFmTopTrack } from "./LastFmTopTrack.ts";
+export type { LastFmHealth } from "./LastFmHealth.ts";
+export type { LastFmUserHealth } from "./LastFmUserHealth.ts";
+export type { SteamLibrary } from "./SteamLibrary.ts";
+export type { SteamPlayedGame } from "./SteamPlayedGame.ts";
+export type { SteamAvatar } from "./SteamAvatar.ts";
+export type { SteamPersonaState } from "./SteamPersonaState.ts";
+export type { SteamAppDetails } from "./SteamAppDetails.ts";
+export type { SteamAchievement } from "./SteamAchievement.ts";
+export type { SteamAchievementProgress } from "./SteamAchievementProgress.ts";
+export type { GitHubEvent } from "./GitHubEvent.ts";
+export type { GitHubStatus } from "./GitHubStatus.ts";
+export type { GitHubUserInfo } from "./GitHubUserInfo.ts";
+export type { CodingStatus } from "./CodingStatus.ts";
+export type { WakaTimeUserInfo } from "./WakaTimeUserInfo.ts";
+export type { MediaKind } from "./MediaKind.ts";
+export type { MediaServer } from "./MediaServer.ts";
+export type { MediaSession } from "./MediaSession.ts";
+export type { FediversePost } from "./FediversePost.ts";
+export type { FediverseUserInfo } from "./FediverseUserInfo.ts";
+export type { BlueskyImage } from "./BlueskyImage.ts";
+export type { BlueskyPost } from "./BlueskyPost.ts";
+export type { BlueskyUserInfo } from "./BlueskyUserInfo.ts";
+export type { TwitchStream } from "./TwitchStream.ts";
+export type { TwitchUserInfo } from "./TwitchUserInfo.ts";
+export type { HomeAssistantActivity } from "./HomeAssistantActivity.ts";
+export type { HomeAssistantUserInfo } from "./HomeAssistantUserInfo.ts";
+export type { CustomStatus } from "./CustomStatus.ts";
+export type { Activity } from "./Activity.ts";
+export type { ActivityKind } from "./ActivityKind.ts";
+export type { ActivitySummary } from "./ActivitySummary.ts";
+export type { WebhookDelivery } from "./WebhookDelivery.ts";
+export type { WebhookEvent } from "./WebhookEvent.ts";
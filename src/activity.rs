@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::config::ActivityConfig;
+
+/// listed in the order they're ranked unless a user says otherwise
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+  Streaming,
+  Gaming,
+  Watching,
+  Voice,
+  Listening,
+  Coding,
+  /// pushed from the user's own scripts
+  Custom,
+  Status,
+}
+
+const DEFAULT_PRIORITY: [ActivityKind; 8] = [
+  ActivityKind::Streaming,
+  ActivityKind::Gaming,
+  ActivityKind::Watching,
+  ActivityKind::Voice,
+  ActivityKind::Listening,
+  ActivityKind::Coding,
+  ActivityKind::Custom,
+  ActivityKind::Status,
+];
+
+#[derive(Clone, Serialize, TS)]
+pub struct Activity {
+  kind: ActivityKind,
+  summary: String,
+}
+
+impl Activity {
+  pub fn kind(&self) -> ActivityKind {
+    self.kind
+  }
+
+  pub fn summary(&self) -> &str {
+    &self.summary
+  }
+}
+
+#[derive(Clone, Default, Serialize, TS)]
+#[ts(rename = "ActivitySummary")]
+pub struct Summary {
+  /// a single line describing the most important thing they're doing
+  summary: Option<String>,
+  /// everything they're doing, most important first
+  signals: Vec<Activity>,
+}
+
+impl Summary {
+  pub fn signals(&self) -> &[Activity] {
+    &self.signals
+  }
+}
+
+/// ranks whichever signals are active, keeping the first of each kind
+pub fn summarize(
+  config: &ActivityConfig,
+  signals: impl IntoIterator<Item = (ActivityKind, Option<String>)>,
+) -> Summary {
+  let rank = |kind: &ActivityKind| {
+    config
+      .priority
+      .iter()
+      .chain(DEFAULT_PRIORITY.iter())
+      .position(|ranked| ranked == kind)
+  };
+
+  let mut seen = HashSet::new();
+  let mut signals = signals
+    .into_iter()
+    .filter(|(kind, _)| !config.hidden.contains(kind))
+    .filter_map(|(kind, summary)| Some((kind, summary?)))
+    .filter(|(kind, _)| seen.insert(*kind))
+    .map(|(kind, summary)| Activity { kind, summary })
+    .collect::<Vec<_>>();
+  // the sort is stable, so signals of the same rank keep the order they were given in
+  signals.sort_by_key(|activity| rank(&activity.kind));
+
+  Summary {
+    summary: signals.first().map(|activity| activity.summary.clone()),
+    signals,
+  }
+}
@@ -1,22 +1,80 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, path::PathBuf, time::Duration};
 
 use axum_extra::{headers::{authorization::Bearer, Authorization}, TypedHeader};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de::Error};
 use steam_rs::steam_id::SteamId;
 
+use crate::{activity::ActivityKind, webhooks::EventKind};
+
 #[derive(Serialize, Deserialize)]
 pub struct Config {
   pub discord_bot_token: Option<String>,
-  pub discord_initial_search_guilds: Vec<u64>,
   pub last_fm_key: Option<String>,
+  #[serde(default = "default_last_fm_recent_track_count")]
+  pub last_fm_recent_track_count: usize,
   pub steam_api_key: Option<String>,
   pub bluebubbles_server: Option<String>,
   pub bluebubbles_server_password: Option<String>,
+  pub jellyfin_server: Option<String>,
+  pub jellyfin_api_key: Option<String>,
+  pub plex_server: Option<String>,
+  pub plex_token: Option<String>,
+  /// any appview speaking the app.bsky lexicons will do
+  #[serde(default = "default_bluesky_appview_url")]
+  pub bluesky_appview_url: String,
+  pub twitch_client_id: Option<String>,
+  pub twitch_client_secret: Option<String>,
+  #[serde(default = "default_twitch_api_url")]
+  pub twitch_api_url: String,
+  #[serde(default = "default_twitch_auth_url")]
+  pub twitch_auth_url: String,
+  pub home_assistant_url: Option<String>,
+  /// a long lived access token from the home assistant profile page
+  pub home_assistant_token: Option<String>,
+  /// a nominatim instance, used to find the country and locality of owntracks locations
+  #[serde(default = "default_reverse_geocoding_url")]
+  pub reverse_geocoding_url: String,
+  #[serde(default)]
+  pub intervals: Intervals,
+  /// where fetchers keep data that should survive a restart
+  #[serde(default = "default_cache_directory")]
+  pub cache_directory: PathBuf,
+
+  /// fetchers defined entirely in config, for services without their own
+  #[serde(default)]
+  pub json_sources: HashMap<String, JsonSourceConfig>,
+  /// subscribers told about status changes, keyed by a name used in the delivery log
+  #[serde(default)]
+  pub webhooks: HashMap<String, WebhookConfig>,
 
   pub auth: HashMap<String, AuthConfig>,
   pub users: HashMap<String, UserConfig>,
 }
 
+fn default_last_fm_recent_track_count() -> usize {
+  10
+}
+
+fn default_cache_directory() -> PathBuf {
+  "cache".into()
+}
+
+fn default_bluesky_appview_url() -> String {
+  "https://public.api.bsky.app".to_owned()
+}
+
+fn default_twitch_api_url() -> String {
+  "https://api.twitch.tv/helix".to_owned()
+}
+
+fn default_twitch_auth_url() -> String {
+  "https://id.twitch.tv/oauth2/token".to_owned()
+}
+
+fn default_reverse_geocoding_url() -> String {
+  "https://nominatim.openstreetmap.org".to_owned()
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct UserConfig {
   pub name: String,
@@ -27,8 +85,308 @@ pub struct UserConfig {
 
   pub discord_id: Option<u64>,
   pub last_fm_username: Option<String>,
+  pub listenbrainz: Option<ListenBrainzConfig>,
   pub steam_id: Option<SteamId>,
   pub icloud_device_id: Option<String>,
+  pub jellyfin_user_id: Option<String>,
+  /// the plex account name, as shown on the server's dashboard
+  pub plex_username: Option<String>,
+  pub github: Option<GitHubConfig>,
+  pub wakatime: Option<WakaTimeConfig>,
+  pub fediverse: Option<FediverseConfig>,
+  pub bluesky_handle: Option<String>,
+  pub twitch_login: Option<String>,
+  pub home_assistant: Option<HomeAssistantConfig>,
+  pub owntracks: Option<OwnTracksConfig>,
+  /// the json sources to fetch for this user, with the variables to fill their templates with
+  #[serde(default)]
+  pub json_sources: HashMap<String, HashMap<String, String>>,
+
+  #[serde(default)]
+  pub activity: ActivityConfig,
+
+  #[serde(default)]
+  pub intervals: UserIntervals,
+}
+
+/// how often to poll a fetcher, in seconds, depending on whether the user is doing anything
+#[derive(Clone, Copy)]
+pub struct PollInterval {
+  pub active: u64,
+  pub idle: u64,
+}
+
+impl PollInterval {
+  pub fn get(&self, active: bool) -> Duration {
+    Duration::from_secs(if active { self.active } else { self.idle })
+  }
+}
+
+/// a [`PollInterval`] as written in the config, where anything left out falls through from the
+/// user's config to the main config and then to the fetcher's default
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PollIntervalOverride {
+  /// while the user is playing or listening to something
+  #[serde(deserialize_with = "some_non_zero")]
+  pub active: Option<u64>,
+  /// while the user is idle or offline
+  #[serde(deserialize_with = "some_non_zero")]
+  pub idle: Option<u64>,
+}
+
+impl PollIntervalOverride {
+  fn or(self, fallback: PollInterval) -> PollInterval {
+    PollInterval {
+      active: self.active.unwrap_or(fallback.active),
+      idle: self.idle.unwrap_or(fallback.idle),
+    }
+  }
+}
+
+/// a zero interval panics `tokio::time::interval` and turns sleep loops into busy loops
+fn non_zero<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+  match u64::deserialize(deserializer)? {
+    0 => Err(D::Error::custom("intervals must be at least one second")),
+    seconds => Ok(seconds),
+  }
+}
+
+fn some_non_zero<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+  non_zero(deserializer).map(Some)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Intervals {
+  pub last_fm: PollIntervalOverride,
+  #[serde(deserialize_with = "non_zero")]
+  pub last_fm_top_lists: u64,
+  pub listenbrainz: PollIntervalOverride,
+  pub steam: PollIntervalOverride,
+  #[serde(deserialize_with = "non_zero")]
+  pub steam_games: u64,
+  #[serde(deserialize_with = "non_zero")]
+  pub steam_app_list: u64,
+  /// a single request covers every device, so this can't be set per user
+  #[serde(deserialize_with = "non_zero")]
+  pub icloud: u64,
+  #[serde(deserialize_with = "non_zero")]
+  pub github: u64,
+  pub wakatime: PollIntervalOverride,
+  /// like icloud, each server's sessions come from a single request
+  #[serde(deserialize_with = "non_zero")]
+  pub media_server: u64,
+  /// also how long a fediverse profile is cached for
+  #[serde(deserialize_with = "non_zero")]
+  pub fediverse: u64,
+  #[serde(deserialize_with = "non_zero")]
+  pub bluesky: u64,
+  #[serde(deserialize_with = "non_zero")]
+  pub twitch: u64,
+  #[serde(deserialize_with = "non_zero")]
+  pub home_assistant: u64,
+  /// how often users are checked for changes to send to webhooks
+  #[serde(deserialize_with = "non_zero")]
+  pub webhooks: u64,
+}
+
+impl Default for Intervals {
+  fn default() -> Self {
+    Intervals {
+      last_fm: Default::default(),
+      last_fm_top_lists: 3600,
+      listenbrainz: Default::default(),
+      steam: Default::default(),
+      steam_games: 3600,
+      steam_app_list: 21600,
+      icloud: 5,
+      github: 300,
+      wakatime: Default::default(),
+      media_server: 10,
+      fediverse: 600,
+      bluesky: 600,
+      twitch: 60,
+      home_assistant: 30,
+      webhooks: 5,
+    }
+  }
+}
+
+impl Intervals {
+  pub fn last_fm_for(&self, user: &UserIntervals) -> PollInterval {
+    user.last_fm.or(self.last_fm.or(PollInterval {
+      active: 10,
+      idle: 30,
+    }))
+  }
+
+  pub fn listenbrainz_for(&self, user: &UserIntervals) -> PollInterval {
+    user.listenbrainz.or(self.listenbrainz.or(PollInterval {
+      active: 10,
+      idle: 30,
+    }))
+  }
+
+  pub fn steam_for(&self, user: &UserIntervals) -> PollInterval {
+    user.steam.or(self.steam.or(PollInterval {
+      active: 30,
+      idle: 60,
+    }))
+  }
+
+  pub fn wakatime_for(&self, user: &UserIntervals) -> PollInterval {
+    user.wakatime.or(self.wakatime.or(PollInterval {
+      active: 60,
+      idle: 300,
+    }))
+  }
+}
+
+/// per user overrides of the [`Intervals`] in the main config
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserIntervals {
+  pub last_fm: PollIntervalOverride,
+  pub listenbrainz: PollIntervalOverride,
+  pub steam: PollIntervalOverride,
+  pub wakatime: PollIntervalOverride,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ListenBrainzConfig {
+  pub username: String,
+  pub token: Option<String>,
+  /// lets users point at a self-hosted instance
+  #[serde(default = "default_listenbrainz_url")]
+  pub base_url: String,
+}
+
+fn default_listenbrainz_url() -> String {
+  "https://api.listenbrainz.org".to_owned()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GitHubConfig {
+  pub username: String,
+  /// needed for the status and contribution count, which are only available over graphql
+  pub token: Option<String>,
+  #[serde(default = "default_github_url")]
+  pub base_url: String,
+}
+
+fn default_github_url() -> String {
+  "https://api.github.com".to_owned()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FediverseConfig {
+  /// in the form @user@instance
+  pub handle: String,
+  /// talk to this instance instead of the one webfinger points at, e.g. a local stand-in
+  pub instance_url: Option<String>,
+  #[serde(default = "default_fediverse_post_count")]
+  pub post_count: u32,
+}
+
+fn default_fediverse_post_count() -> u32 {
+  5
+}
+
+/// entity ids to read, e.g. `person.alice` or `sensor.alices_phone_battery_level`
+#[derive(Serialize, Deserialize)]
+pub struct HomeAssistantConfig {
+  /// gives the zone and coordinates
+  pub person: Option<String>,
+  /// the companion app's geocoded location sensor, which gives the country and locality,
+  /// otherwise the person's coordinates are reverse geocoded
+  pub geocoded_location: Option<String>,
+  pub battery: Option<String>,
+  pub battery_state: Option<String>,
+  pub focus: Option<String>,
+  /// anything describing what their computer is up to, shown by its friendly name
+  #[serde(default)]
+  pub activity: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OwnTracksConfig {
+  /// the app's http mode sends this along with the config username
+  pub password: String,
+  /// zones to report when the device doesn't say it's in one of its own regions
+  #[serde(default)]
+  pub regions: Vec<OwnTracksRegion>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OwnTracksRegion {
+  pub name: String,
+  pub latitude: f64,
+  pub longitude: f64,
+  /// in meters
+  pub radius: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WakaTimeConfig {
+  pub api_key: String,
+  /// for wakapi, this is the instance's `/api/compat/wakatime/v1`
+  #[serde(default = "default_wakatime_url")]
+  pub base_url: String,
+}
+
+fn default_wakatime_url() -> String {
+  "https://wakatime.com/api/v1".to_owned()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonSourceConfig {
+  /// `{variable}`s are filled in from the user's config, percent-encoded, along with `{username}`
+  pub url: String,
+  /// templated the same way as the url, but left unencoded
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+  /// in seconds
+  #[serde(
+    default = "default_json_source_interval",
+    deserialize_with = "non_zero"
+  )]
+  pub interval: u64,
+  /// field names mapped to paths into the response, like `data.items[0].name`
+  pub fields: HashMap<String, String>,
+}
+
+fn default_json_source_interval() -> u64 {
+  60
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WebhookConfig {
+  pub url: String,
+  /// payloads are signed with this, sent as `X-Signature-256: sha256=<hex hmac>`
+  pub secret: String,
+  /// empty lists let everything through
+  #[serde(default)]
+  pub events: Vec<EventKind>,
+  #[serde(default)]
+  pub users: Vec<String>,
+  /// only applies to activity events
+  #[serde(default)]
+  pub activities: Vec<ActivityKind>,
+  /// what the subscriber gets to see, like an auth token, so watching and voice activity
+  /// need `media.watching` and `discord.voice`
+  #[serde(default)]
+  pub scopes: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct ActivityConfig {
+  /// ranked ahead of everything else, in this order
+  #[serde(default)]
+  pub priority: Vec<ActivityKind>,
+  /// never shown in the activity summary, though they're still in their own sections
+  #[serde(default)]
+  pub hidden: Vec<ActivityKind>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -43,6 +401,6 @@ pub fn scopes_from_bearer(bearer: Option<TypedHeader<Authorization<Bearer>>>, co
     .unwrap_or_default()
 }
 
-pub fn has_scope(auth_scopes: &Cow<[String]>, scope: &'static str) -> bool{
+pub fn has_scope(auth_scopes: &[String], scope: &'static str) -> bool {
   auth_scopes.iter().any(|s| scope == s)
 }
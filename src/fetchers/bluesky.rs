@@ -0,0 +1,253 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+  time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use ts_rs::TS;
+
+use crate::config::Config;
+
+const POST_COUNT: u32 = 5;
+
+#[derive(Clone, Serialize, TS)]
+pub struct BlueskyUserInfo {
+  handle: String,
+  did: String,
+  display_name: Option<String>,
+  description: Option<String>,
+  avatar: Option<String>,
+  banner: Option<String>,
+  followers: u64,
+  follows: u64,
+  post_count: u64,
+  posts: Vec<BlueskyPost>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct BlueskyPost {
+  url: String,
+  created_at: DateTime<Utc>,
+  text: String,
+  images: Vec<BlueskyImage>,
+  replies: u64,
+  reposts: u64,
+  likes: u64,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct BlueskyImage {
+  thumbnail: String,
+  full_size: String,
+  alt: String,
+}
+
+static USERS: LazyLock<RwLock<HashMap<String, BlueskyUserInfo>>> = LazyLock::new(Default::default);
+
+pub fn fetch_bluesky_info(handle: &str) -> Option<BlueskyUserInfo> {
+  USERS.read().unwrap().get(handle).cloned()
+}
+
+pub fn run(config: &'static Config) {
+  let mut started = false;
+  for user in config.users.values() {
+    let Some(handle) = &user.bluesky_handle else {
+      continue;
+    };
+
+    started = true;
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.bluesky));
+
+      loop {
+        interval.tick().await;
+        update_user(&config.bluesky_appview_url, handle).await;
+      }
+    });
+  }
+
+  if started {
+    tracing::info!("started bluesky fetcher");
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawProfile {
+  did: String,
+  handle: String,
+  display_name: Option<String>,
+  description: Option<String>,
+  avatar: Option<String>,
+  banner: Option<String>,
+  #[serde(default)]
+  followers_count: u64,
+  #[serde(default)]
+  follows_count: u64,
+  #[serde(default)]
+  posts_count: u64,
+}
+
+#[derive(Deserialize)]
+struct RawFeed {
+  feed: Vec<RawFeedItem>,
+}
+
+#[derive(Deserialize)]
+struct RawFeedItem {
+  post: RawPost,
+  /// set when the post shows up for some other reason than being posted, like a repost or a pin
+  reason: Option<RawReason>,
+}
+
+#[derive(Deserialize)]
+struct RawReason {
+  #[serde(rename = "$type")]
+  kind: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPost {
+  uri: String,
+  record: RawRecord,
+  embed: Option<RawEmbed>,
+  #[serde(default)]
+  reply_count: u64,
+  #[serde(default)]
+  repost_count: u64,
+  #[serde(default)]
+  like_count: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawRecord {
+  #[serde(default)]
+  text: String,
+  created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct RawEmbed {
+  #[serde(default)]
+  images: Vec<RawImage>,
+}
+
+#[derive(Deserialize)]
+struct RawImage {
+  thumb: String,
+  fullsize: String,
+  #[serde(default)]
+  alt: String,
+}
+
+async fn xrpc<T: DeserializeOwned>(
+  appview_url: &str,
+  method: &str,
+  params: &[(&str, &str)],
+) -> anyhow::Result<T> {
+  let url = Url::parse_with_params(&format!("{appview_url}/xrpc/{method}"), params)?;
+  Ok(
+    reqwest::Client::new()
+      .get(url)
+      .send()
+      .await?
+      .error_for_status()?
+      .json()
+      .await?,
+  )
+}
+
+async fn update_user(appview_url: &str, handle: &str) {
+  let post_count = POST_COUNT.to_string();
+  let profile_params = [("actor", handle)];
+  let feed_params = [
+    ("actor", handle),
+    ("limit", post_count.as_str()),
+    ("filter", "posts_no_replies"),
+  ];
+  let (profile, feed) = futures::join!(
+    xrpc::<RawProfile>(appview_url, "app.bsky.actor.getProfile", &profile_params),
+    xrpc::<RawFeed>(appview_url, "app.bsky.feed.getAuthorFeed", &feed_params),
+  );
+
+  let profile = match profile {
+    Ok(profile) => profile,
+    Err(error) => {
+      tracing::error!("failed to request bluesky profile for {handle}: {error}");
+      return;
+    }
+  };
+  let feed = match feed {
+    Ok(feed) => feed,
+    Err(error) => {
+      tracing::error!("failed to request bluesky feed for {handle}: {error}");
+      return;
+    }
+  };
+
+  let posts = feed
+    .feed
+    .into_iter()
+    // pinned posts are still their own, only reposts of other people's posts are left out
+    .filter(|item| {
+      item
+        .reason
+        .as_ref()
+        .is_none_or(|reason| reason.kind != "app.bsky.feed.defs#reasonRepost")
+    })
+    .map(|item| {
+      let post = item.post;
+      // at uris end in the record key, which is also what the web app uses
+      let record_key = post.uri.rsplit('/').next().unwrap_or_default();
+
+      BlueskyPost {
+        url: format!(
+          "https://bsky.app/profile/{}/post/{record_key}",
+          profile.handle
+        ),
+        created_at: post.record.created_at,
+        text: post.record.text,
+        images: post
+          .embed
+          .map(|embed| {
+            embed
+              .images
+              .into_iter()
+              .map(|image| BlueskyImage {
+                thumbnail: image.thumb,
+                full_size: image.fullsize,
+                alt: image.alt,
+              })
+              .collect()
+          })
+          .unwrap_or_default(),
+        replies: post.reply_count,
+        reposts: post.repost_count,
+        likes: post.like_count,
+      }
+    })
+    .collect();
+
+  USERS.write().unwrap().insert(
+    handle.to_owned(),
+    BlueskyUserInfo {
+      handle: profile.handle,
+      did: profile.did,
+      display_name: profile.display_name.filter(|name| !name.is_empty()),
+      description: profile
+        .description
+        .filter(|description| !description.is_empty()),
+      avatar: profile.avatar,
+      banner: profile.banner,
+      followers: profile.followers_count,
+      follows: profile.follows_count,
+      post_count: profile.posts_count,
+      posts,
+    },
+  );
+}
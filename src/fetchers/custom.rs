@@ -0,0 +1,103 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+/// how long a pushed status lasts when the pusher doesn't say
+const DEFAULT_TTL: u64 = 300;
+/// anything pushing status is expected to keep refreshing it
+const MAX_TTL: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Serialize, TS)]
+pub struct CustomStatus {
+  text: Option<String>,
+  emoji: Option<String>,
+  url: Option<String>,
+  /// whatever else the source wants to pass along
+  data: Option<Value>,
+  updated_at: DateTime<Utc>,
+  expires_at: DateTime<Utc>,
+}
+
+impl CustomStatus {
+  pub fn activity(&self) -> Option<String> {
+    match (&self.emoji, &self.text) {
+      (Some(emoji), Some(text)) => Some(format!("{emoji} {text}")),
+      (None, Some(text)) => Some(text.clone()),
+      _ => None,
+    }
+  }
+}
+
+/// the most recently pushed status with something to show
+pub fn latest_activity(statuses: &HashMap<String, CustomStatus>) -> Option<String> {
+  statuses
+    .values()
+    .filter_map(|status| Some((status.updated_at, status.activity()?)))
+    .max_by_key(|(updated_at, _)| *updated_at)
+    .map(|(_, activity)| activity)
+}
+
+/// what a user's own scripts send to `PUT /user/{user}/status/{source}`
+#[derive(Deserialize)]
+pub struct PushedStatus {
+  /// in seconds, a ttl of 0 clears the status
+  ttl: Option<u64>,
+  text: Option<String>,
+  emoji: Option<String>,
+  url: Option<String>,
+  data: Option<Value>,
+}
+
+/// keyed by username, then by source
+static USERS: LazyLock<RwLock<HashMap<String, HashMap<String, CustomStatus>>>> =
+  LazyLock::new(Default::default);
+
+pub fn get_user_info(username: &str) -> HashMap<String, CustomStatus> {
+  let now = Utc::now();
+
+  USERS
+    .read()
+    .unwrap()
+    .get(username)
+    .map(|sources| {
+      sources
+        .iter()
+        .filter(|(_, status)| status.expires_at > now)
+        .map(|(source, status)| (source.clone(), status.clone()))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+pub fn push_status(username: &str, source: &str, status: PushedStatus) {
+  let now = Utc::now();
+  let ttl = status.ttl.unwrap_or(DEFAULT_TTL).min(MAX_TTL);
+
+  let mut users = USERS.write().unwrap();
+  let sources = users.entry(username.to_owned()).or_default();
+  // expired statuses are only hidden on read, so this is where they get cleaned up
+  sources.retain(|_, status| status.expires_at > now);
+
+  if ttl == 0 {
+    sources.remove(source);
+    return;
+  }
+
+  sources.insert(
+    source.to_owned(),
+    CustomStatus {
+      text: status.text,
+      emoji: status.emoji,
+      url: status.url,
+      data: status.data,
+      updated_at: now,
+      expires_at: now + TimeDelta::seconds(ttl as i64),
+    },
+  );
+}
@@ -1,40 +1,73 @@
 use std::{
-  collections::HashMap,
+  collections::{HashMap, HashSet},
   panic::AssertUnwindSafe,
-  sync::{LazyLock, RwLock},
-  time::Duration,
+  sync::{Arc, LazyLock, RwLock},
+  time::{Duration, Instant},
 };
 
+use chrono::{DateTime, SubsecRound, Utc};
 use futures::FutureExt;
 use serde::Serialize;
 use serenity::all::{
-  ActivityEmoji, ActivityType, CacheHttp, ChunkGuildFilter, ClientStatus, Context, EventHandler,
-  GatewayIntents, GuildMembersChunkEvent, OnlineStatus, Presence, Ready,
+  ActivityEmoji, ActivityType, CacheHttp, ChunkGuildFilter, ClientStatus, ConnectionStage, Context,
+  Event, EventHandler, GatewayIntents, Guild, GuildId, GuildMembersChunkEvent, Member,
+  OnlineStatus, Presence, RawEventHandler, ShardManager, ShardStageUpdateEvent, UnavailableGuild,
+  User, UserId, VoiceState,
 };
-use tracing::info;
+use tracing::{error, info, warn};
 use ts_rs::TS;
 
-use crate::config::Config;
+use crate::config::{Config, has_scope};
 
 pub async fn run_discord_bot(config: &Config) -> anyhow::Result<()> {
-  let Some(token) = config.discord_bot_token.as_ref().map(String::as_str) else {
+  let Some(token) = config.discord_bot_token.as_deref() else {
     return Ok(());
   };
 
   let mut client = serenity::Client::builder(
     token,
-    GatewayIntents::GUILD_PRESENCES | GatewayIntents::GUILD_MEMBERS,
+    GatewayIntents::GUILDS
+      | GatewayIntents::GUILD_PRESENCES
+      | GatewayIntents::GUILD_MEMBERS
+      | GatewayIntents::GUILD_VOICE_STATES,
   )
-  .event_handler(Handler(config.discord_initial_search_guilds.clone()))
+  .event_handler(Handler(
+    config
+      .users
+      .values()
+      .filter_map(|user| user.discord_id)
+      .collect(),
+  ))
+  .raw_event_handler(EventRecorder)
   .await?;
 
+  tokio::spawn(watch_latency(client.shard_manager.clone()));
+
   tokio::spawn(async move {
+    let mut backoff = MIN_BACKOFF;
     loop {
-      if let Err(error) = AssertUnwindSafe(client.start()).catch_unwind().await {
-        eprintln!("serenity client crashed: {error:#?}");
+      let started = Instant::now();
+      match AssertUnwindSafe(client.start()).catch_unwind().await {
+        Ok(Ok(())) => warn!("serenity client stopped"),
+        Ok(Err(error)) => error!("serenity client failed: {error:#?}"),
+        Err(error) => error!("serenity client crashed: {error:#?}"),
+      }
+
+      // a client that stayed up for a while isn't part of a crash loop
+      if started.elapsed() > HEALTHY_RUN {
+        backoff = MIN_BACKOFF;
       }
 
-      tokio::time::sleep(Duration::from_secs(30)).await
+      {
+        let mut health = HEALTH.write().unwrap();
+        health.set_state(GatewayState::Disconnected);
+        health.latency_ms = None;
+        health.restarts += 1;
+      }
+
+      warn!("restarting serenity client in {backoff:?}");
+      tokio::time::sleep(backoff).await;
+      backoff = (backoff * 2).min(MAX_BACKOFF);
     }
   });
 
@@ -43,7 +76,106 @@ pub async fn run_discord_bot(config: &Config) -> anyhow::Result<()> {
   Ok(())
 }
 
+const MIN_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+const HEALTHY_RUN: Duration = Duration::from_secs(10 * 60);
+/// how long the gateway can be down before we stop trusting the presences we have
+const STALE_AFTER: chrono::TimeDelta = chrono::TimeDelta::seconds(60);
+
+#[derive(Clone, Copy, PartialEq, Serialize, TS)]
+#[ts(rename = "DiscordGatewayState")]
+pub enum GatewayState {
+  #[serde(rename = "connected")]
+  Connected,
+  #[serde(rename = "connecting")]
+  Connecting,
+  #[serde(rename = "disconnected")]
+  Disconnected,
+}
+
+impl From<ConnectionStage> for GatewayState {
+  fn from(stage: ConnectionStage) -> Self {
+    match stage {
+      ConnectionStage::Connected => GatewayState::Connected,
+      ConnectionStage::Disconnected => GatewayState::Disconnected,
+      _ => GatewayState::Connecting,
+    }
+  }
+}
+
+#[derive(Clone, Serialize, TS)]
+#[ts(rename = "DiscordGatewayHealth")]
+pub struct GatewayHealth {
+  state: GatewayState,
+  latency_ms: Option<u64>,
+  last_event: Option<DateTime<Utc>>,
+  disconnected_since: Option<DateTime<Utc>>,
+  restarts: u32,
+}
+
+impl GatewayHealth {
+  fn set_state(&mut self, state: GatewayState) {
+    if state == GatewayState::Connected {
+      self.disconnected_since = None;
+    } else if self.disconnected_since.is_none() {
+      self.disconnected_since = Some(Utc::now().round_subsecs(0));
+    }
+
+    self.state = state;
+  }
+
+  fn is_stale(&self) -> bool {
+    self
+      .disconnected_since
+      .is_some_and(|since| Utc::now() - since > STALE_AFTER)
+  }
+}
+
+static HEALTH: LazyLock<RwLock<GatewayHealth>> = LazyLock::new(|| {
+  RwLock::new(GatewayHealth {
+    state: GatewayState::Connecting,
+    latency_ms: None,
+    last_event: None,
+    disconnected_since: Some(Utc::now().round_subsecs(0)),
+    restarts: 0,
+  })
+});
+
+pub fn fetch_health() -> GatewayHealth {
+  HEALTH.read().unwrap().clone()
+}
+
+async fn watch_latency(shard_manager: Arc<ShardManager>) {
+  let mut interval = tokio::time::interval(Duration::from_secs(30));
+
+  loop {
+    interval.tick().await;
+
+    let latency = shard_manager
+      .runners
+      .lock()
+      .await
+      .values()
+      .find_map(|runner| runner.latency);
+    HEALTH.write().unwrap().latency_ms = latency.map(|latency| latency.as_millis() as u64);
+  }
+}
+
+/// records when we last heard anything from the gateway
+struct EventRecorder;
+
+#[async_trait::async_trait]
+impl RawEventHandler for EventRecorder {
+  async fn raw_event(&self, _: Context, _: Event) {
+    HEALTH.write().unwrap().last_event = Some(Utc::now().round_subsecs(0));
+  }
+}
+
 static USERS: LazyLock<RwLock<HashMap<u64, DiscordUserInfo>>> = LazyLock::new(Default::default);
+/// the guilds each tracked user was last seen in, so we know when to forget them
+static MEMBERSHIPS: LazyLock<RwLock<HashMap<u64, HashSet<u64>>>> = LazyLock::new(Default::default);
+static VOICE_CHANNELS: LazyLock<RwLock<HashMap<u64, VoiceChannel>>> =
+  LazyLock::new(Default::default);
 
 #[derive(Clone, Serialize, TS)]
 #[ts(rename = "DiscordEmoji")]
@@ -87,6 +219,17 @@ pub enum TypescriptOnlineStatus {
   Online,
 }
 
+#[derive(Clone, Serialize, TS)]
+#[ts(rename = "DiscordVoiceChannel")]
+pub struct VoiceChannel {
+  guild_name: String,
+  channel_name: String,
+  muted: bool,
+  deafened: bool,
+  streaming: bool,
+  video: bool,
+}
+
 #[derive(Clone, Serialize, TS)]
 pub struct DiscordUserInfo {
   display_name: String,
@@ -95,13 +238,103 @@ pub struct DiscordUserInfo {
   #[ts(as = "Option<TypescriptOnlineStatus>")]
   client_status: Option<ClientStatus>,
   custom_status: Option<CustomStatus>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  voice: Option<VoiceChannel>,
+  /// set when the gateway has been down long enough that this info may be outdated
+  stale: bool,
+}
+
+impl DiscordUserInfo {
+  pub fn is_online(&self) -> bool {
+    !matches!(self.status, OnlineStatus::Offline | OnlineStatus::Invisible)
+  }
+
+  /// only set when voice channels were visible to whoever's asking
+  pub fn voice_activity(&self) -> Option<String> {
+    let voice = self.voice.as_ref()?;
+    Some(format!("in {} on {}", voice.channel_name, voice.guild_name))
+  }
+
+  pub fn status_activity(&self) -> Option<String> {
+    let custom_status = self.custom_status.as_ref()?;
+    let emoji = match &custom_status.emoji {
+      Some(Emoji::Official { name }) => Some(name.as_str()),
+      _ => None,
+    };
+
+    match (emoji, &custom_status.text) {
+      (Some(emoji), Some(text)) => Some(format!("{emoji} {text}")),
+      (None, Some(text)) => Some(text.clone()),
+      (Some(emoji), None) => Some(emoji.to_owned()),
+      (None, None) => None,
+    }
+  }
+}
+
+pub fn fetch_user_info(user_id: u64, auth_scopes: &[String]) -> Option<DiscordUserInfo> {
+  let mut info = USERS.read().unwrap().get(&user_id).cloned()?;
+  info.stale = HEALTH.read().unwrap().is_stale();
+  if has_scope(auth_scopes, "discord.voice") {
+    info.voice = VOICE_CHANNELS.read().unwrap().get(&user_id).cloned();
+  }
+
+  Some(info)
+}
+
+fn build_voice_channel(guild: &Guild, voice_state: &VoiceState) -> Option<VoiceChannel> {
+  let channel = guild.channels.get(&voice_state.channel_id?)?;
+
+  Some(VoiceChannel {
+    guild_name: guild.name.clone(),
+    channel_name: channel.name.clone(),
+    muted: voice_state.mute || voice_state.self_mute,
+    deafened: voice_state.deaf || voice_state.self_deaf,
+    streaming: voice_state.self_stream.unwrap_or_default(),
+    video: voice_state.self_video,
+  })
+}
+
+fn update_voice_channel(user_id: UserId, voice_channel: Option<VoiceChannel>) {
+  let mut voice_channels = VOICE_CHANNELS.write().unwrap();
+  match voice_channel {
+    Some(voice_channel) => voice_channels.insert(user_id.get(), voice_channel),
+    None => voice_channels.remove(&user_id.get()),
+  };
 }
 
-pub fn fetch_user_info(user_id: u64) -> Option<DiscordUserInfo> {
-  USERS.read().unwrap().get(&user_id).cloned()
+fn record_membership(guild_id: GuildId, user_id: UserId) {
+  MEMBERSHIPS
+    .write()
+    .unwrap()
+    .entry(user_id.get())
+    .or_default()
+    .insert(guild_id.get());
 }
 
-struct Handler(Vec<u64>);
+/// the discord ids of all configured users
+struct Handler(HashSet<u64>);
+
+impl Handler {
+  fn is_tracked(&self, user_id: UserId) -> bool {
+    self.0.contains(&user_id.get())
+  }
+
+  /// removes a guild from a user's memberships, dropping their info once they share no guilds with us
+  fn forget_membership(&self, guild_id: GuildId, user_id: UserId) {
+    // keep the lock order the same as guild_members_chunk (USERS, then MEMBERSHIPS)
+    let mut users = USERS.write().unwrap();
+    let mut memberships = MEMBERSHIPS.write().unwrap();
+    let Some(guilds) = memberships.get_mut(&user_id.get()) else {
+      return;
+    };
+
+    guilds.remove(&guild_id.get());
+    if guilds.is_empty() {
+      memberships.remove(&user_id.get());
+      users.remove(&user_id.get());
+    }
+  }
+}
 
 async fn build_user_info(ctx: &impl CacheHttp, presence: Presence) -> Option<DiscordUserInfo> {
   let display_name = if let Some(user) = ctx.cache().and_then(|cache| cache.user(presence.user.id))
@@ -158,17 +391,69 @@ async fn build_user_info(ctx: &impl CacheHttp, presence: Presence) -> Option<Dis
     status: presence.status,
     client_status: presence.client_status,
     custom_status,
+    voice: None,
+    stale: false,
   })
 }
 
 #[async_trait::async_trait]
 impl EventHandler for Handler {
-  async fn ready(&self, ctx: Context, _: Ready) {
-    for guild in self.0.iter().cloned() {
-      info!("checking guild {guild}");
-      ctx
-        .shard
-        .chunk_guild(guild.into(), None, true, ChunkGuildFilter::None, None)
+  async fn shard_stage_update(&self, _: Context, event: ShardStageUpdateEvent) {
+    info!("discord shard is now {}", event.new);
+    HEALTH.write().unwrap().set_state(event.new.into());
+  }
+
+  async fn guild_create(&self, ctx: Context, guild: Guild, _: Option<bool>) {
+    info!("checking guild {}", guild.id);
+    for voice_state in guild.voice_states.values() {
+      if self.is_tracked(voice_state.user_id) {
+        update_voice_channel(
+          voice_state.user_id,
+          build_voice_channel(&guild, voice_state),
+        );
+      }
+    }
+
+    let user_ids = self.0.iter().cloned().map(UserId::new).collect::<Vec<_>>();
+
+    // discord only returns up to 100 members per user id query
+    for user_ids in user_ids.chunks(100) {
+      ctx.shard.chunk_guild(
+        guild.id,
+        None,
+        true,
+        ChunkGuildFilter::UserIds(user_ids.to_vec()),
+        None,
+      )
+    }
+  }
+
+  async fn guild_delete(&self, _: Context, incomplete: UnavailableGuild, _: Option<Guild>) {
+    // an unavailable guild is an outage, not us leaving it
+    if incomplete.unavailable {
+      return;
+    }
+
+    for user_id in self.0.iter().cloned() {
+      self.forget_membership(incomplete.id, user_id.into());
+    }
+  }
+
+  async fn guild_member_addition(&self, _: Context, member: Member) {
+    if self.is_tracked(member.user.id) {
+      record_membership(member.guild_id, member.user.id);
+    }
+  }
+
+  async fn guild_member_removal(
+    &self,
+    _: Context,
+    guild_id: GuildId,
+    user: User,
+    _: Option<Member>,
+  ) {
+    if self.is_tracked(user.id) {
+      self.forget_membership(guild_id, user.id);
     }
   }
 
@@ -177,7 +462,16 @@ impl EventHandler for Handler {
     // prevent USERS guard from making the function !Send
     {
       let mut users = USERS.write().unwrap();
+      let mut memberships = MEMBERSHIPS.write().unwrap();
       for member in chunk.members.values() {
+        if !self.is_tracked(member.user.id) {
+          continue;
+        }
+
+        memberships
+          .entry(member.user.id.get())
+          .or_default()
+          .insert(chunk.guild_id.get());
         users
           .entry(member.user.id.get())
           .or_insert_with(|| DiscordUserInfo {
@@ -185,6 +479,8 @@ impl EventHandler for Handler {
             status: OnlineStatus::Offline,
             client_status: None,
             custom_status: None,
+            voice: None,
+            stale: false,
           });
       }
     }
@@ -196,8 +492,28 @@ impl EventHandler for Handler {
 
   async fn presence_update(&self, ctx: Context, presence: Presence) {
     let user_id = presence.user.id;
+    if !self.is_tracked(user_id) {
+      return;
+    }
+
+    if let Some(guild_id) = presence.guild_id {
+      record_membership(guild_id, user_id);
+    }
+
     if let Some(presence) = build_user_info(&ctx, presence).await {
       USERS.write().unwrap().insert(user_id.into(), presence);
     }
   }
+
+  async fn voice_state_update(&self, ctx: Context, _: Option<VoiceState>, voice_state: VoiceState) {
+    if !self.is_tracked(voice_state.user_id) {
+      return;
+    }
+
+    let voice_channel = voice_state
+      .guild_id
+      .and_then(|guild_id| ctx.cache.guild(guild_id))
+      .and_then(|guild| build_voice_channel(&guild, &voice_state));
+    update_voice_channel(voice_state.user_id, voice_channel);
+  }
 }
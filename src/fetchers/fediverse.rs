@@ -0,0 +1,278 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+  time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use ts_rs::TS;
+
+use crate::config::{Config, FediverseConfig};
+
+#[derive(Clone, Serialize, TS)]
+pub struct FediverseUserInfo {
+  handle: String,
+  display_name: String,
+  url: String,
+  avatar: String,
+  /// html, as rendered by the instance
+  bio: String,
+  followers: u64,
+  following: u64,
+  posts: Vec<FediversePost>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct FediversePost {
+  url: Option<String>,
+  created_at: DateTime<Utc>,
+  /// html, as rendered by the instance
+  content: String,
+  content_warning: Option<String>,
+  media: Vec<String>,
+  replies: u64,
+  boosts: u64,
+  favourites: u64,
+}
+
+static USERS: LazyLock<RwLock<HashMap<String, FediverseUserInfo>>> =
+  LazyLock::new(Default::default);
+
+pub fn fetch_fediverse_info(handle: &str) -> Option<FediverseUserInfo> {
+  USERS.read().unwrap().get(handle).cloned()
+}
+
+pub fn run(config: &'static Config) {
+  let mut started = false;
+  for user in config.users.values() {
+    let Some(fediverse) = &user.fediverse else {
+      continue;
+    };
+
+    started = true;
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.fediverse));
+      let mut account = None;
+
+      loop {
+        interval.tick().await;
+
+        // the handle only needs resolving once, unless the account it pointed at goes away
+        if account.is_none() {
+          account = resolve_account(fediverse).await;
+        }
+        let Some(resolved) = &account else {
+          continue;
+        };
+
+        if !update_user(fediverse, resolved).await {
+          account = None;
+        }
+      }
+    });
+  }
+
+  if started {
+    tracing::info!("started fediverse fetcher");
+  }
+}
+
+/// where the account actually lives, which isn't always the domain in the handle
+struct ResolvedAccount {
+  instance: String,
+  id: String,
+}
+
+#[derive(Deserialize)]
+struct WebFinger {
+  #[serde(default)]
+  links: Vec<WebFingerLink>,
+}
+
+#[derive(Deserialize)]
+struct WebFingerLink {
+  rel: String,
+  #[serde(rename = "type")]
+  kind: Option<String>,
+  href: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawAccount {
+  id: String,
+  acct: String,
+  display_name: String,
+  url: String,
+  avatar: String,
+  note: String,
+  followers_count: u64,
+  following_count: u64,
+}
+
+#[derive(Deserialize)]
+struct RawStatus {
+  url: Option<String>,
+  created_at: DateTime<Utc>,
+  visibility: String,
+  content: String,
+  spoiler_text: String,
+  #[serde(default)]
+  media_attachments: Vec<RawAttachment>,
+  replies_count: u64,
+  reblogs_count: u64,
+  favourites_count: u64,
+}
+
+#[derive(Deserialize)]
+struct RawAttachment {
+  url: String,
+}
+
+async fn get<T: DeserializeOwned>(url: Url) -> reqwest::Result<T> {
+  reqwest::Client::new()
+    .get(url)
+    .header("Accept", "application/json")
+    .send()
+    .await?
+    .error_for_status()?
+    .json()
+    .await
+}
+
+async fn resolve_account(fediverse: &FediverseConfig) -> Option<ResolvedAccount> {
+  let handle = &fediverse.handle;
+  let acct = handle.trim_start_matches('@');
+  let Some((_, domain)) = acct.split_once('@') else {
+    tracing::error!("fediverse handle {handle} should look like @user@instance");
+    return None;
+  };
+
+  let webfinger_base = fediverse
+    .instance_url
+    .clone()
+    .unwrap_or_else(|| format!("https://{domain}"));
+  let url = Url::parse_with_params(
+    &format!("{webfinger_base}/.well-known/webfinger"),
+    [("resource", format!("acct:{acct}"))],
+  )
+  .inspect_err(|error| tracing::error!("invalid webfinger url for {handle}: {error}"))
+  .ok()?;
+
+  let webfinger = get::<WebFinger>(url)
+    .await
+    .inspect_err(|error| tracing::error!("failed to resolve fediverse handle {handle}: {error}"))
+    .ok()?;
+
+  // webfinger may be served from a different domain than the instance itself, so follow the actor link
+  let instance = match &fediverse.instance_url {
+    Some(instance_url) => instance_url.clone(),
+    None => {
+      let actor = webfinger.links.into_iter().find_map(|link| {
+        (link.rel == "self" && link.kind.as_deref() == Some("application/activity+json"))
+          .then_some(link.href)
+          .flatten()
+      });
+      let Some(actor) = actor.and_then(|actor| Url::parse(&actor).ok()) else {
+        tracing::error!("webfinger for fediverse handle {handle} has no actor link");
+        return None;
+      };
+      actor.origin().ascii_serialization()
+    }
+  };
+
+  let url = Url::parse_with_params(
+    &format!("{instance}/api/v1/accounts/lookup"),
+    [("acct", acct)],
+  )
+  .inspect_err(|error| tracing::error!("invalid account lookup url for {handle}: {error}"))
+  .ok()?;
+
+  let account = get::<RawAccount>(url)
+    .await
+    .inspect_err(|error| tracing::error!("failed to look up fediverse account {handle}: {error}"))
+    .ok()?;
+
+  tracing::debug!(
+    "resolved fediverse handle {handle} to {} on {instance}",
+    account.acct
+  );
+  Some(ResolvedAccount {
+    instance,
+    id: account.id,
+  })
+}
+
+/// returns false when the account should be resolved again
+async fn update_user(fediverse: &FediverseConfig, resolved: &ResolvedAccount) -> bool {
+  let handle = &fediverse.handle;
+  let ResolvedAccount { instance, id } = resolved;
+
+  let account = async {
+    get::<RawAccount>(Url::parse(&format!("{instance}/api/v1/accounts/{id}")).ok()?)
+      .await
+      .inspect_err(|error| {
+        tracing::error!("failed to request fediverse profile for {handle}: {error}")
+      })
+      .ok()
+  };
+  let statuses = async {
+    let url = Url::parse_with_params(
+      &format!("{instance}/api/v1/accounts/{id}/statuses"),
+      [
+        ("limit", fediverse.post_count.to_string().as_str()),
+        ("exclude_replies", "true"),
+        ("exclude_reblogs", "true"),
+      ],
+    )
+    .ok()?;
+
+    get::<Vec<RawStatus>>(url)
+      .await
+      .inspect_err(|error| {
+        tracing::error!("failed to request fediverse posts for {handle}: {error}")
+      })
+      .ok()
+  };
+
+  let (Some(account), Some(statuses)) = futures::join!(account, statuses) else {
+    return false;
+  };
+
+  let posts = statuses
+    .into_iter()
+    // unlisted and followers only posts come back too when the instance knows who's asking
+    .filter(|status| status.visibility == "public")
+    .map(|status| FediversePost {
+      url: status.url,
+      created_at: status.created_at,
+      content: status.content,
+      content_warning: (!status.spoiler_text.is_empty()).then_some(status.spoiler_text),
+      media: status
+        .media_attachments
+        .into_iter()
+        .map(|attachment| attachment.url)
+        .collect(),
+      replies: status.replies_count,
+      boosts: status.reblogs_count,
+      favourites: status.favourites_count,
+    })
+    .collect();
+
+  USERS.write().unwrap().insert(
+    handle.clone(),
+    FediverseUserInfo {
+      handle: handle.clone(),
+      display_name: account.display_name,
+      url: account.url,
+      avatar: account.avatar,
+      bio: account.note,
+      followers: account.followers_count,
+      following: account.following_count,
+      posts,
+    },
+  );
+
+  true
+}
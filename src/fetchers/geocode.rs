@@ -0,0 +1,69 @@
+use serde::Deserialize;
+
+use crate::config::Config;
+
+/// how far someone has to move before their country and locality are looked up again, in meters
+pub const REGEOCODE_DISTANCE: f64 = 1000.0;
+
+/// great circle distance in meters
+pub fn distance(
+  (latitude_a, longitude_a): (f64, f64),
+  (latitude_b, longitude_b): (f64, f64),
+) -> f64 {
+  const EARTH_RADIUS: f64 = 6_371_000.0;
+
+  let (latitude_a, latitude_b) = (latitude_a.to_radians(), latitude_b.to_radians());
+  let delta_latitude = latitude_b - latitude_a;
+  let delta_longitude = (longitude_b - longitude_a).to_radians();
+
+  let a = (delta_latitude / 2.0).sin().powi(2)
+    + latitude_a.cos() * latitude_b.cos() * (delta_longitude / 2.0).sin().powi(2);
+  2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+#[derive(Deserialize)]
+struct ReverseGeocode {
+  address: Address,
+}
+
+#[derive(Deserialize)]
+struct Address {
+  country: String,
+  city: Option<String>,
+  town: Option<String>,
+  village: Option<String>,
+  municipality: Option<String>,
+}
+
+/// the country and locality around a point, looked up with nominatim
+pub async fn reverse_geocode(
+  config: &Config,
+  latitude: f64,
+  longitude: f64,
+) -> reqwest::Result<(String, String)> {
+  let response = reqwest::Client::new()
+    .get(format!("{}/reverse", config.reverse_geocoding_url))
+    .query(&[
+      ("format", "jsonv2"),
+      ("zoom", "10"),
+      ("lat", latitude.to_string().as_str()),
+      ("lon", longitude.to_string().as_str()),
+    ])
+    // nominatim turns away requests without a user agent
+    .header("User-Agent", "personal-api")
+    .send()
+    .await?
+    .error_for_status()?
+    .json::<ReverseGeocode>()
+    .await?;
+
+  let address = response.address;
+  let locality = address
+    .city
+    .or(address.town)
+    .or(address.village)
+    .or(address.municipality)
+    .unwrap_or_default();
+
+  Ok((address.country, locality))
+}
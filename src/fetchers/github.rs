@@ -0,0 +1,293 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+  time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use ts_rs::TS;
+
+use crate::config::{Config, GitHubConfig};
+
+#[derive(Clone, Serialize, TS)]
+pub struct GitHubUserInfo {
+  username: String,
+  status: Option<GitHubStatus>,
+  /// over the last year, only available with a token
+  contributions: Option<u64>,
+  events: Vec<GitHubEvent>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct GitHubStatus {
+  emoji: Option<String>,
+  message: Option<String>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub enum GitHubEvent {
+  Push {
+    repo: String,
+    created_at: DateTime<Utc>,
+    branch: String,
+    commits: Option<u64>,
+  },
+  PullRequest {
+    repo: String,
+    created_at: DateTime<Utc>,
+    action: String,
+    number: u64,
+    title: String,
+    url: String,
+  },
+  Release {
+    repo: String,
+    created_at: DateTime<Utc>,
+    tag: String,
+    name: Option<String>,
+    url: String,
+  },
+}
+
+static USERS: LazyLock<RwLock<HashMap<String, GitHubUserInfo>>> = LazyLock::new(Default::default);
+
+pub fn fetch_github_info(username: &str) -> Option<GitHubUserInfo> {
+  USERS.read().unwrap().get(username).cloned()
+}
+
+pub fn run(config: &'static Config) {
+  let mut started = false;
+  for user in config.users.values() {
+    let Some(github) = &user.github else {
+      continue;
+    };
+
+    started = true;
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.github));
+
+      loop {
+        interval.tick().await;
+        update_user(github).await;
+      }
+    });
+  }
+
+  if started {
+    tracing::info!("started github fetcher");
+  }
+}
+
+#[derive(Deserialize)]
+struct RawEvent {
+  #[serde(rename = "type")]
+  kind: String,
+  repo: RawRepo,
+  created_at: DateTime<Utc>,
+  payload: Value,
+}
+
+#[derive(Deserialize)]
+struct RawRepo {
+  name: String,
+}
+
+#[derive(Deserialize)]
+struct PushPayload {
+  #[serde(rename = "ref")]
+  git_ref: String,
+  size: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct PullRequestPayload {
+  action: String,
+  number: u64,
+  pull_request: PullRequest,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+  title: String,
+  html_url: String,
+}
+
+#[derive(Deserialize)]
+struct ReleasePayload {
+  release: Release,
+}
+
+#[derive(Deserialize)]
+struct Release {
+  tag_name: String,
+  name: Option<String>,
+  html_url: String,
+}
+
+impl RawEvent {
+  /// only keeps the kinds of events worth showing off
+  fn into_event(self) -> Option<GitHubEvent> {
+    let repo = self.repo.name;
+    let created_at = self.created_at;
+
+    Some(match self.kind.as_str() {
+      "PushEvent" => {
+        let payload = serde_json::from_value::<PushPayload>(self.payload).ok()?;
+        GitHubEvent::Push {
+          repo,
+          created_at,
+          branch: payload
+            .git_ref
+            .strip_prefix("refs/heads/")
+            .unwrap_or(&payload.git_ref)
+            .to_owned(),
+          commits: payload.size,
+        }
+      }
+      "PullRequestEvent" => {
+        let payload = serde_json::from_value::<PullRequestPayload>(self.payload).ok()?;
+        GitHubEvent::PullRequest {
+          repo,
+          created_at,
+          action: payload.action,
+          number: payload.number,
+          title: payload.pull_request.title,
+          url: payload.pull_request.html_url,
+        }
+      }
+      "ReleaseEvent" => {
+        let payload = serde_json::from_value::<ReleasePayload>(self.payload).ok()?;
+        GitHubEvent::Release {
+          repo,
+          created_at,
+          tag: payload.release.tag_name,
+          name: payload.release.name,
+          url: payload.release.html_url,
+        }
+      }
+      _ => return None,
+    })
+  }
+}
+
+#[derive(Deserialize)]
+struct GraphQlBase {
+  data: GraphQlData,
+}
+
+#[derive(Deserialize)]
+struct GraphQlData {
+  user: Option<GraphQlUser>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlUser {
+  status: Option<GraphQlStatus>,
+  contributions_collection: ContributionsCollection,
+}
+
+#[derive(Deserialize)]
+struct GraphQlStatus {
+  emoji: Option<String>,
+  message: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsCollection {
+  contribution_calendar: ContributionCalendar,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionCalendar {
+  total_contributions: u64,
+}
+
+const PROFILE_QUERY: &str = "query($login: String!) {
+  user(login: $login) {
+    status { emoji message }
+    contributionsCollection { contributionCalendar { totalContributions } }
+  }
+}";
+
+fn request(github: &GitHubConfig, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+  // github turns away requests without a user agent
+  let request = reqwest::Client::new()
+    .request(method, format!("{}/{path}", github.base_url))
+    .header("User-Agent", "personal-api")
+    .header("Accept", "application/vnd.github+json");
+
+  match &github.token {
+    Some(token) => request.bearer_auth(token),
+    None => request,
+  }
+}
+
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> reqwest::Result<T> {
+  request.send().await?.error_for_status()?.json().await
+}
+
+async fn update_user(github: &GitHubConfig) {
+  let username = &github.username;
+  let events = send::<Vec<RawEvent>>(request(
+    github,
+    reqwest::Method::GET,
+    &format!("users/{username}/events/public?per_page=30"),
+  ));
+
+  // status and contributions are only exposed through graphql, which always needs a token
+  let profile = async {
+    github.token.as_ref()?;
+
+    send::<GraphQlBase>(
+      request(github, reqwest::Method::POST, "graphql").json(&json!({
+        "query": PROFILE_QUERY,
+        "variables": { "login": username },
+      })),
+    )
+    .await
+    .inspect_err(|error| {
+      tracing::error!("failed to request github profile for user {username}: {error}")
+    })
+    .ok()?
+    .data
+    .user
+  };
+
+  let (events, profile) = futures::join!(events, profile);
+  let events = match events {
+    Ok(events) => events
+      .into_iter()
+      .filter_map(RawEvent::into_event)
+      .collect(),
+    Err(error) => {
+      tracing::error!("failed to request github events for user {username}: {error}");
+      return;
+    }
+  };
+
+  USERS.write().unwrap().insert(
+    username.clone(),
+    GitHubUserInfo {
+      username: username.clone(),
+      status: profile
+        .as_ref()
+        .and_then(|profile| profile.status.as_ref())
+        .map(|status| GitHubStatus {
+          emoji: status.emoji.clone(),
+          message: status.message.clone(),
+        }),
+      contributions: profile.map(|profile| {
+        profile
+          .contributions_collection
+          .contribution_calendar
+          .total_contributions
+      }),
+      events,
+    },
+  );
+}
@@ -0,0 +1,261 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+  time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use ts_rs::TS;
+
+use crate::{
+  config::{Config, HomeAssistantConfig, has_scope},
+  fetchers::{
+    geocode::{REGEOCODE_DISTANCE, distance, reverse_geocode},
+    icloud::{DeviceInfo, Location},
+  },
+};
+
+#[derive(Clone, Serialize, TS)]
+pub struct HomeAssistantUserInfo {
+  /// phone battery percentage
+  battery: Option<u8>,
+  charging: Option<bool>,
+  /// whether a focus or do not disturb mode is on
+  focus: Option<bool>,
+  activity: Vec<HomeAssistantActivity>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct HomeAssistantActivity {
+  name: String,
+  state: String,
+  since: DateTime<Utc>,
+}
+
+struct UserState {
+  info: HomeAssistantUserInfo,
+  location: Option<DeviceInfo>,
+}
+
+/// keyed by the user's name in the config
+static USERS: LazyLock<RwLock<HashMap<String, UserState>>> = LazyLock::new(Default::default);
+
+pub fn fetch_home_assistant_info(
+  username: &str,
+  auth_scopes: &[String],
+) -> Option<HomeAssistantUserInfo> {
+  if !has_scope(auth_scopes, "home_assistant.device") {
+    return None;
+  }
+
+  USERS
+    .read()
+    .unwrap()
+    .get(username)
+    .map(|user| user.info.clone())
+}
+
+pub fn get_location(username: &str, auth_scopes: &[String]) -> Option<Location> {
+  USERS
+    .read()
+    .unwrap()
+    .get(username)?
+    .location
+    .as_ref()
+    .map(|location| location.to_location(auth_scopes))
+}
+
+pub fn run(config: &'static Config) {
+  let Some((server, token)) = config
+    .home_assistant_url
+    .as_ref()
+    .zip(config.home_assistant_token.as_ref())
+  else {
+    return;
+  };
+
+  let users = config
+    .users
+    .iter()
+    .filter_map(|(username, user)| Some((username.as_str(), user.home_assistant.as_ref()?)))
+    .collect::<Vec<_>>();
+  if users.is_empty() {
+    return;
+  }
+
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.home_assistant));
+
+    loop {
+      interval.tick().await;
+
+      // one request for every entity is cheaper than one per entity once a few users are set up
+      let states = match request_states(server, token).await {
+        Ok(states) => states
+          .into_iter()
+          .map(|state| (state.entity_id.clone(), state))
+          .collect::<HashMap<_, _>>(),
+        Err(error) => {
+          tracing::error!("failed to request home assistant states: {error}");
+          continue;
+        }
+      };
+
+      for (username, home_assistant) in &users {
+        let user = read_user(config, username, home_assistant, &states).await;
+        USERS.write().unwrap().insert(username.to_string(), user);
+      }
+    }
+  });
+
+  tracing::info!("started home assistant fetcher");
+}
+
+#[derive(Deserialize)]
+struct EntityState {
+  entity_id: String,
+  state: String,
+  #[serde(default)]
+  attributes: Map<String, Value>,
+  last_changed: DateTime<Utc>,
+}
+
+impl EntityState {
+  fn attribute(&self, name: &str) -> Option<&Value> {
+    self.attributes.get(name)
+  }
+
+  fn friendly_name(&self) -> &str {
+    self
+      .attribute("friendly_name")
+      .and_then(Value::as_str)
+      .unwrap_or(&self.entity_id)
+  }
+}
+
+async fn request_states(server: &str, token: &str) -> reqwest::Result<Vec<EntityState>> {
+  reqwest::Client::new()
+    .get(format!("{server}/api/states"))
+    .bearer_auth(token)
+    .send()
+    .await?
+    .error_for_status()?
+    .json()
+    .await
+}
+
+async fn read_user(
+  config: &Config,
+  username: &str,
+  home_assistant: &HomeAssistantConfig,
+  states: &HashMap<String, EntityState>,
+) -> UserState {
+  // entities that are offline report these instead of a real state
+  let entity = |entity_id: &Option<String>| {
+    states
+      .get(entity_id.as_ref()?)
+      .filter(|state| !matches!(state.state.as_str(), "unavailable" | "unknown"))
+  };
+
+  let person = entity(&home_assistant.person);
+  let coordinates = person.and_then(|person| {
+    let coordinate = |name| person.attribute(name).and_then(Value::as_f64);
+    Some((coordinate("latitude")?, coordinate("longitude")?))
+  });
+
+  let location = match person.zip(coordinates) {
+    Some((person, (latitude, longitude))) => {
+      let address = match entity(&home_assistant.geocoded_location) {
+        Some(geocoded) => {
+          let address = |name| {
+            geocoded
+              .attribute(name)
+              .and_then(Value::as_str)
+              .map(str::to_owned)
+          };
+          address("Country").map(|country| (country, address("Locality").unwrap_or_default()))
+        }
+        // without the companion app's sensor, look the person's coordinates up ourselves
+        None => geocode_person(config, username, latitude, longitude).await,
+      };
+
+      address.map(|(country, locality)| DeviceInfo {
+        country,
+        locality,
+        latitude,
+        longitude,
+        // not_home is what home assistant calls being outside every zone
+        zone: (person.state != "not_home").then(|| match person.state.as_str() {
+          "home" => "Home".to_owned(),
+          zone => zone.to_owned(),
+        }),
+      })
+    }
+    None => None,
+  };
+
+  let battery_state = entity(&home_assistant.battery_state).map(|state| state.state.as_str());
+  let info = HomeAssistantUserInfo {
+    battery: entity(&home_assistant.battery)
+      .and_then(|state| state.state.parse::<f64>().ok())
+      .map(|level| level.round().clamp(0.0, 100.0) as u8),
+    // the ios and android apps disagree on what charging is called
+    charging: battery_state.map(|state| {
+      matches!(
+        state.to_ascii_lowercase().as_str(),
+        "charging" | "full" | "wireless"
+      )
+    }),
+    focus: entity(&home_assistant.focus).map(|state| state.state == "on"),
+    activity: home_assistant
+      .activity
+      .iter()
+      .filter_map(|entity_id| {
+        let state = states
+          .get(entity_id)
+          .filter(|state| !matches!(state.state.as_str(), "unavailable" | "unknown" | "off"))?;
+
+        Some(HomeAssistantActivity {
+          name: state.friendly_name().to_owned(),
+          state: state.state.clone(),
+          since: state.last_changed,
+        })
+      })
+      .collect(),
+  };
+
+  UserState { info, location }
+}
+
+/// reuses the last address while they stay nearby, since nominatim asks for a request a second at most
+async fn geocode_person(
+  config: &Config,
+  username: &str,
+  latitude: f64,
+  longitude: f64,
+) -> Option<(String, String)> {
+  let previous = USERS
+    .read()
+    .unwrap()
+    .get(username)
+    .and_then(|user| user.location.as_ref())
+    .filter(|previous| {
+      distance(
+        (previous.latitude, previous.longitude),
+        (latitude, longitude),
+      ) < REGEOCODE_DISTANCE
+    })
+    .map(|previous| (previous.country.clone(), previous.locality.clone()));
+  if previous.is_some() {
+    return previous;
+  }
+
+  reverse_geocode(config, latitude, longitude)
+    .await
+    .inspect_err(|error| {
+      tracing::error!("failed to reverse geocode home assistant location for {username}: {error}")
+    })
+    .ok()
+}
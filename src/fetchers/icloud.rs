@@ -18,6 +18,8 @@ pub struct Location {
   #[serde(skip_serializing_if = "Option::is_none")]
   locality: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
+  zone: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   latitude: Option<f64>,
   #[serde(skip_serializing_if = "Option::is_none")]
   longitude: Option<f64>,
@@ -26,11 +28,33 @@ pub struct Location {
   pub time_zone: Option<&'static str>,
 }
 
-struct DeviceInfo {
-  country: String,
-  locality: String,
-  latitude: f64,
-  longitude: f64,
+/// shared with the other location sources so they all go through the same scopes
+pub struct DeviceInfo {
+  pub country: String,
+  pub locality: String,
+  pub latitude: f64,
+  pub longitude: f64,
+  /// a named place like home or work, when the source knows about one
+  pub zone: Option<String>,
+}
+
+impl DeviceInfo {
+  pub fn to_location(&self, auth_scopes: &[String]) -> Location {
+    Location {
+      country: self.country.clone(),
+      locality: has_scope(auth_scopes, "icloud.city")
+        .then_some(&self.locality)
+        .cloned(),
+      zone: has_scope(auth_scopes, "icloud.city")
+        .then_some(self.zone.as_ref())
+        .flatten()
+        .cloned(),
+      latitude: has_scope(auth_scopes, "icloud.latlong").then_some(self.latitude),
+      longitude: has_scope(auth_scopes, "icloud.latlong").then_some(self.longitude),
+      time_zone: has_scope(auth_scopes, "icloud.latlong")
+        .then(|| FINDER.get_tz_name(self.longitude, self.latitude)),
+    }
+  }
 }
 
 static DEVICE_INFO: LazyLock<RwLock<HashMap<String, DeviceInfo>>> = LazyLock::new(RwLock::default);
@@ -41,16 +65,7 @@ pub fn get_user_info(device_id: &str, auth_scopes: Cow<'static, [String]>) -> Op
     .read()
     .unwrap()
     .get(device_id)
-    .map(|location| Location {
-      country: location.country.clone(),
-      locality: has_scope(&auth_scopes, "icloud.city")
-        .then_some(&location.locality)
-        .cloned(),
-      latitude: has_scope(&auth_scopes, "icloud.latlong").then_some(location.latitude),
-      longitude: has_scope(&auth_scopes, "icloud.latlong").then_some(location.longitude),
-      time_zone: has_scope(&auth_scopes, "icloud.latlong")
-        .then(|| FINDER.get_tz_name(location.longitude, location.latitude)),
-    })
+    .map(|location| location.to_location(&auth_scopes))
 }
 
 pub fn run(config: &'static Config) {
@@ -66,7 +81,7 @@ pub fn run(config: &'static Config) {
   info!("started icloud fetcher");
 
   tokio::spawn(async move {
-    let mut interval = tokio::time::interval(Duration::from_secs(5));
+    let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.icloud));
 
     loop {
       interval.tick().await;
@@ -95,6 +110,7 @@ pub fn run(config: &'static Config) {
               locality: address.locality,
               latitude: location.latitude,
               longitude: location.longitude,
+              zone: None,
             },
           );
         } else {
@@ -0,0 +1,288 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+  time::Duration,
+};
+
+use serde_json::Value;
+
+use crate::config::{Config, JsonSourceConfig};
+
+/// keyed by source name, then by field
+type SourceValues = HashMap<String, HashMap<String, Value>>;
+
+/// keyed by username
+static USERS: LazyLock<RwLock<HashMap<String, SourceValues>>> = LazyLock::new(Default::default);
+
+pub fn get_user_info(username: &str) -> SourceValues {
+  USERS
+    .read()
+    .unwrap()
+    .get(username)
+    .cloned()
+    .unwrap_or_default()
+}
+
+pub fn run(config: &'static Config) {
+  let mut started = false;
+  for (username, user) in &config.users {
+    for (name, variables) in &user.json_sources {
+      let Some(source) = config.json_sources.get(name) else {
+        tracing::error!("user {username} uses json source {name}, which isn't defined");
+        continue;
+      };
+
+      // the username is always available, on top of whatever the user sets
+      let mut variables = variables.clone();
+      variables
+        .entry("username".to_owned())
+        .or_insert_with(|| username.clone());
+
+      let (url, headers) = match fill_request(source, &variables) {
+        Ok(request) => request,
+        Err(variable) => {
+          tracing::error!(
+            "json source {name} needs the variable {variable}, which user {username} doesn't set"
+          );
+          continue;
+        }
+      };
+      let fields = match source
+        .fields
+        .iter()
+        .map(|(field, path)| Ok((field.as_str(), to_pointer(path)?)))
+        .collect::<Result<Vec<_>, String>>()
+      {
+        Ok(fields) => fields,
+        Err(path) => {
+          tracing::error!("json source {name} has an invalid path {path}");
+          continue;
+        }
+      };
+
+      started = true;
+      tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(source.interval));
+
+        loop {
+          interval.tick().await;
+
+          let response = match send(&url, &headers).await {
+            Ok(response) => response,
+            Err(error) => {
+              tracing::error!("failed to request json source {name} for user {username}: {error}");
+              continue;
+            }
+          };
+
+          let values = fields
+            .iter()
+            .filter_map(|(field, pointer)| {
+              Some((field.to_string(), response.pointer(pointer)?.clone()))
+            })
+            .collect();
+
+          USERS
+            .write()
+            .unwrap()
+            .entry(username.clone())
+            .or_default()
+            .insert(name.clone(), values);
+        }
+      });
+    }
+  }
+
+  if started {
+    tracing::info!("started json source fetcher");
+  }
+}
+
+async fn send(url: &str, headers: &[(String, String)]) -> reqwest::Result<Value> {
+  let mut request = reqwest::Client::new().get(url);
+  for (header, value) in headers {
+    request = request.header(header, value);
+  }
+
+  request.send().await?.error_for_status()?.json().await
+}
+
+/// fills in the url and headers once, so each tick only has to build a request from them
+fn fill_request(
+  source: &JsonSourceConfig,
+  variables: &HashMap<String, String>,
+) -> Result<(String, Vec<(String, String)>), String> {
+  let url = fill_template(&source.url, variables, percent_encode)?;
+  let headers = source
+    .headers
+    .iter()
+    .map(|(header, value)| {
+      Ok((
+        header.clone(),
+        fill_template(value, variables, str::to_owned)?,
+      ))
+    })
+    .collect::<Result<_, String>>()?;
+
+  Ok((url, headers))
+}
+
+/// encodes everything urls don't leave unreserved, so a variable can't add path segments or parameters
+fn percent_encode(value: &str) -> String {
+  let mut encoded = String::with_capacity(value.len());
+  for byte in value.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+        encoded.push(byte as char)
+      }
+      byte => encoded.push_str(&format!("%{byte:02X}")),
+    }
+  }
+
+  encoded
+}
+
+/// replaces every `{variable}`, or returns the name of the first one that isn't set
+fn fill_template(
+  template: &str,
+  variables: &HashMap<String, String>,
+  encode: fn(&str) -> String,
+) -> Result<String, String> {
+  let mut filled = String::with_capacity(template.len());
+  let mut rest = template;
+
+  while let Some(start) = rest.find('{') {
+    let Some(end) = rest[start..].find('}') else {
+      break;
+    };
+
+    let variable = &rest[start + 1..start + end];
+    filled.push_str(&rest[..start]);
+    filled.push_str(&encode(
+      variables.get(variable).ok_or_else(|| variable.to_owned())?,
+    ));
+    rest = &rest[start + end + 1..];
+  }
+
+  filled.push_str(rest);
+  Ok(filled)
+}
+
+/// turns a path like `data.items[0].name` into a json pointer like `/data/items/0/name`
+fn to_pointer(path: &str) -> Result<String, String> {
+  let path = path.strip_prefix("$").unwrap_or(path);
+  let mut pointer = String::new();
+
+  for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+    let (key, mut indices) = match segment.find('[') {
+      Some(index) => segment.split_at(index),
+      None => (segment, ""),
+    };
+
+    if !key.is_empty() {
+      pointer.push('/');
+      pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    }
+
+    while let Some(index) = indices.strip_prefix('[') {
+      let Some((index, rest)) = index.split_once(']') else {
+        return Err(path.to_owned());
+      };
+      if index.parse::<usize>().is_err() {
+        return Err(path.to_owned());
+      }
+
+      pointer.push('/');
+      pointer.push_str(index);
+      indices = rest;
+    }
+
+    if !indices.is_empty() {
+      return Err(path.to_owned());
+    }
+  }
+
+  Ok(pointer)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn variables() -> HashMap<String, String> {
+    HashMap::from([
+      ("username".to_owned(), "alice".to_owned()),
+      ("query".to_owned(), "a b/c?d=e&f".to_owned()),
+    ])
+  }
+
+  #[test]
+  fn fills_variables() {
+    assert_eq!(
+      fill_template(
+        "https://example.com/{username}/stats",
+        &variables(),
+        str::to_owned
+      ),
+      Ok("https://example.com/alice/stats".to_owned())
+    );
+  }
+
+  #[test]
+  fn encodes_url_variables() {
+    assert_eq!(
+      fill_template(
+        "https://example.com/search?q={query}",
+        &variables(),
+        percent_encode
+      ),
+      Ok("https://example.com/search?q=a%20b%2Fc%3Fd%3De%26f".to_owned())
+    );
+    assert_eq!(
+      fill_template("Bearer {query}", &variables(), str::to_owned),
+      Ok("Bearer a b/c?d=e&f".to_owned())
+    );
+  }
+
+  #[test]
+  fn reports_unknown_variables() {
+    assert_eq!(
+      fill_template("https://example.com/{token}", &variables(), str::to_owned),
+      Err("token".to_owned())
+    );
+  }
+
+  #[test]
+  fn leaves_unterminated_braces() {
+    assert_eq!(
+      fill_template(
+        "https://example.com/{username}/{oops",
+        &variables(),
+        str::to_owned
+      ),
+      Ok("https://example.com/alice/{oops".to_owned())
+    );
+  }
+
+  #[test]
+  fn converts_paths_to_pointers() {
+    assert_eq!(
+      to_pointer("data.items[0].name"),
+      Ok("/data/items/0/name".to_owned())
+    );
+    assert_eq!(to_pointer("$.matrix[1][2]"), Ok("/matrix/1/2".to_owned()));
+    assert_eq!(to_pointer("[3].id"), Ok("/3/id".to_owned()));
+  }
+
+  #[test]
+  fn escapes_pointer_keys() {
+    assert_eq!(to_pointer("a~b.c/d"), Ok("/a~0b/c~1d".to_owned()));
+  }
+
+  #[test]
+  fn rejects_bad_indices() {
+    assert!(to_pointer("items[one]").is_err());
+    assert!(to_pointer("items[0").is_err());
+    assert!(to_pointer("items[0]name").is_err());
+  }
+}
@@ -1,18 +1,19 @@
 use std::{
   collections::HashMap,
+  fmt::{self, Display},
   sync::{LazyLock, RwLock},
   time::Duration,
 };
 
-use chrono::{DateTime, SubsecRound, Utc};
-use futures::{FutureExt, TryFutureExt, future::join_all};
+use chrono::{DateTime, SubsecRound, TimeDelta, Utc};
 use lastfm::{
   artist::Artist,
   imageset::ImageSet,
-  track::{NowPlayingTrack, Track},
+  track::{NowPlayingTrack, RecordedTrack, Track},
 };
-use reqwest::Response;
-use serde::{Deserialize, Serialize};
+use reqwest::{IntoUrl, Url};
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
+use tokio::{sync::Mutex, time::Instant};
 use ts_rs::TS;
 
 use crate::config::Config;
@@ -48,6 +49,13 @@ pub struct TypescriptTrack {
   pub album: String,
   pub url: String,
   pub start_time: DateTime<Utc>,
+  pub loved: Option<bool>,
+  /// the length of the track in milliseconds
+  pub duration: Option<u64>,
+  /// when the track should finish, assuming it's played through from `start_time`
+  pub end_time: Option<DateTime<Utc>>,
+  pub user_playcount: Option<u64>,
+  pub tags: Vec<String>,
 }
 
 impl PartialEq<NowPlayingTrack> for TypescriptTrack {
@@ -60,22 +68,266 @@ impl PartialEq<NowPlayingTrack> for TypescriptTrack {
   }
 }
 
+#[allow(unused)]
+#[derive(Clone, Serialize, TS)]
+#[ts(rename = "LastFmRecordedTrack")]
+pub struct TypescriptRecordedTrack {
+  #[ts(as = "TypescriptArtist")]
+  pub artist: Artist,
+  pub name: String,
+  #[ts(as = "TypescriptImageSet")]
+  pub image: ImageSet,
+  pub album: String,
+  pub url: String,
+  pub date: DateTime<Utc>,
+}
+
+impl From<RecordedTrack> for TypescriptRecordedTrack {
+  fn from(track: RecordedTrack) -> Self {
+    TypescriptRecordedTrack {
+      artist: track.artist,
+      name: track.name,
+      image: track.image,
+      album: track.album,
+      url: track.url,
+      date: track.date,
+    }
+  }
+}
+
+#[derive(Clone, Deserialize, Serialize, TS)]
+#[ts(rename = "LastFmTopArtist")]
+pub struct TopArtist {
+  name: String,
+  url: String,
+  #[serde(deserialize_with = "number_from_string")]
+  playcount: u64,
+  #[ts(as = "TypescriptImageSet")]
+  image: ImageSet,
+}
+
+#[derive(Clone, Deserialize, Serialize, TS)]
+#[ts(rename = "LastFmTopTrack")]
+pub struct TopTrack {
+  name: String,
+  url: String,
+  #[serde(deserialize_with = "name_of")]
+  artist: String,
+  #[serde(deserialize_with = "number_from_string")]
+  playcount: u64,
+  #[ts(as = "TypescriptImageSet")]
+  image: ImageSet,
+}
+
+#[derive(Clone, Deserialize, Serialize, TS)]
+#[ts(rename = "LastFmTopAlbum")]
+pub struct TopAlbum {
+  name: String,
+  url: String,
+  #[serde(deserialize_with = "name_of")]
+  artist: String,
+  #[serde(deserialize_with = "number_from_string")]
+  playcount: u64,
+  #[ts(as = "TypescriptImageSet")]
+  image: ImageSet,
+}
+
+#[derive(Clone, Default, Serialize, TS)]
+#[ts(rename = "LastFmTopList")]
+pub struct TopList {
+  artists: Vec<TopArtist>,
+  tracks: Vec<TopTrack>,
+  albums: Vec<TopAlbum>,
+}
+
+#[derive(Clone, Default, Serialize, TS)]
+#[ts(rename = "LastFmTopLists")]
+pub struct TopLists {
+  seven_days: TopList,
+  one_month: TopList,
+  overall: TopList,
+}
+
 #[derive(Clone, Serialize, TS)]
 #[ts(rename = "LastFmUserInfo")]
 pub struct UserInfo {
-  username: String,
-  currently_playing: Option<TypescriptTrack>,
+  pub username: String,
+  pub currently_playing: Option<TypescriptTrack>,
+  pub recent_tracks: Vec<TypescriptRecordedTrack>,
+  pub scrobble_count: Option<u64>,
+  pub top: TopLists,
+}
+
+impl UserInfo {
+  pub fn activity(&self) -> Option<String> {
+    let track = self.currently_playing.as_ref()?;
+    Some(format!(
+      "listening to {} by {}",
+      track.name, track.artist.name
+    ))
+  }
 }
 
 static PLAYING_TRACKS: LazyLock<RwLock<HashMap<String, UserInfo>>> =
   LazyLock::new(Default::default);
 
+/// (username, artist, track name)
+type TrackKey = (String, String, String);
+
+#[derive(Clone)]
+struct TrackInfo {
+  loved: bool,
+  duration: Option<u64>,
+  user_playcount: Option<u64>,
+  tags: Vec<String>,
+}
+
+struct CachedTrackInfo {
+  /// missing when last.fm couldn't find the track, so it isn't asked again every poll
+  info: Option<TrackInfo>,
+  fetched_at: DateTime<Utc>,
+}
+
+static TRACK_INFO: LazyLock<RwLock<HashMap<TrackKey, CachedTrackInfo>>> =
+  LazyLock::new(Default::default);
+const TRACK_INFO_LIFETIME: TimeDelta = TimeDelta::hours(1);
+const TRACK_INFO_CAPACITY: usize = 1024;
+
 pub fn fetch_lastfm_info(username: &str) -> Option<UserInfo> {
   PLAYING_TRACKS.read().unwrap().get(username).cloned()
 }
 
-struct User {
-  username: String,
+/// the minimum gap between any two last.fm requests, so bursts get spread out
+const REQUEST_SPACING: Duration = Duration::from_millis(250);
+const MIN_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+const ERROR_SUSPENDED_KEY: u32 = 26;
+const ERROR_RATE_LIMITED: u32 = 29;
+
+#[derive(Clone, Default, Serialize, TS)]
+#[ts(rename = "LastFmUserHealth")]
+pub struct UserHealth {
+  last_success: Option<DateTime<Utc>>,
+  last_error: Option<String>,
+  last_error_time: Option<DateTime<Utc>>,
+  consecutive_errors: u32,
+}
+
+#[derive(Clone, Default, Serialize, TS)]
+#[ts(rename = "LastFmHealth")]
+pub struct Health {
+  rate_limited_until: Option<DateTime<Utc>>,
+  users: HashMap<String, UserHealth>,
+}
+
+static HEALTH: LazyLock<RwLock<Health>> = LazyLock::new(Default::default);
+static BACKOFF: LazyLock<RwLock<Duration>> = LazyLock::new(|| RwLock::new(MIN_BACKOFF));
+/// every request takes its turn through here, which keeps them spaced out and paused while rate limited
+static NEXT_REQUEST: LazyLock<Mutex<Instant>> = LazyLock::new(|| Mutex::new(Instant::now()));
+
+pub fn fetch_health() -> Health {
+  HEALTH.read().unwrap().clone()
+}
+
+fn record_result<T>(username: &str, result: &Result<T, FetchError>) {
+  let mut health = HEALTH.write().unwrap();
+  let user = health.users.entry(username.to_owned()).or_default();
+  match result {
+    Ok(_) => {
+      user.last_success = Some(Utc::now().round_subsecs(0));
+      user.consecutive_errors = 0;
+    }
+    Err(error) => {
+      user.last_error = Some(error.to_string());
+      user.last_error_time = Some(Utc::now().round_subsecs(0));
+      user.consecutive_errors += 1;
+    }
+  }
+}
+
+#[derive(Debug)]
+enum FetchError {
+  Request(reqwest::Error),
+  Api { code: u32, message: String },
+}
+
+impl Display for FetchError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FetchError::Request(error) => write!(f, "{error}"),
+      FetchError::Api { code, message } => write!(f, "last.fm error {code}: {message}"),
+    }
+  }
+}
+
+/// last.fm reports most errors with a 200 and an error body instead of the expected response
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApiResponse<T> {
+  Error { error: u32, message: String },
+  Ok(T),
+}
+
+async fn wait_for_turn() {
+  let mut next_request = NEXT_REQUEST.lock().await;
+  let rate_limited_for = HEALTH
+    .read()
+    .unwrap()
+    .rate_limited_until
+    .and_then(|until| (until - Utc::now()).to_std().ok())
+    .unwrap_or_default();
+
+  tokio::time::sleep_until((*next_request).max(Instant::now() + rate_limited_for)).await;
+  *next_request = Instant::now() + REQUEST_SPACING;
+}
+
+async fn api_request<T: DeserializeOwned>(url: impl IntoUrl) -> Result<T, FetchError> {
+  wait_for_turn().await;
+
+  // the url carries the api key, and these errors end up on the public health route
+  let response = reqwest::get(url)
+    .await
+    .map_err(|error| FetchError::Request(error.without_url()))?
+    .json::<ApiResponse<T>>()
+    .await
+    .map_err(|error| FetchError::Request(error.without_url()))?;
+
+  match response {
+    ApiResponse::Ok(response) => {
+      let was_rate_limited = HEALTH.write().unwrap().rate_limited_until.take().is_some();
+      if was_rate_limited {
+        *BACKOFF.write().unwrap() = MIN_BACKOFF;
+      }
+
+      Ok(response)
+    }
+    ApiResponse::Error { error, message } => {
+      if error == ERROR_RATE_LIMITED || error == ERROR_SUSPENDED_KEY {
+        let backoff = {
+          let mut backoff = BACKOFF.write().unwrap();
+          // a suspended key won't come back any time soon, so don't bother ramping up
+          if error == ERROR_SUSPENDED_KEY {
+            *backoff = MAX_BACKOFF;
+          }
+
+          let current = *backoff;
+          *backoff = (current * 2).min(MAX_BACKOFF);
+          current
+        };
+
+        tracing::warn!("last.fm is refusing requests ({message}), backing off for {backoff:?}");
+        HEALTH.write().unwrap().rate_limited_until = Some(
+          Utc::now().round_subsecs(0) + TimeDelta::from_std(backoff).expect("backoff is small"),
+        );
+      }
+
+      Err(FetchError::Api {
+        code: error,
+        message,
+      })
+    }
+  }
 }
 
 pub async fn run(config: &'static Config) {
@@ -94,6 +346,9 @@ pub async fn run(config: &'static Config) {
           username: last_fm_username,
           currently_playing: None,
           // currently_playing_recorded: None,
+          recent_tracks: Vec::new(),
+          scrobble_count: None,
+          top: TopLists::default(),
         },
       ))
     })
@@ -102,31 +357,50 @@ pub async fn run(config: &'static Config) {
   let users = config
     .users
     .values()
-    .filter_map(|config| {
-      let last_fm_username = config.last_fm_username.clone()?;
-
-      Some(User {
-        username: last_fm_username,
-      })
+    .filter_map(|user| {
+      let interval = config.intervals.last_fm_for(&user.intervals);
+      Some((user.last_fm_username.as_deref()?, interval))
     })
     .collect::<Vec<_>>();
 
-  let perform_update = async move || {
-    join_all(
-      users
-        .iter()
-        .map(|user| update_currently_listening(&user.username, &last_fm_key)),
-    )
-    .map(drop)
-    .await;
-  };
+  if users.is_empty() {
+    return;
+  }
 
-  perform_update().await;
+  let recent_track_count = config.last_fm_recent_track_count;
+  let top_usernames = users
+    .iter()
+    .map(|(username, _)| *username)
+    .collect::<Vec<_>>();
+  tokio::spawn(async move {
+    let mut schedule = users
+      .into_iter()
+      .map(|(username, interval)| (username, interval, Instant::now()))
+      .collect::<Vec<_>>();
+
+    loop {
+      let (username, interval, next_poll) = schedule
+        .iter_mut()
+        .min_by_key(|(_, _, next_poll)| *next_poll)
+        .expect("there is at least one user");
+
+      tokio::time::sleep_until(*next_poll).await;
+      // failing to check counts as idle, so errors don't get retried at the faster rate
+      let playing = update_recent_tracks(username, last_fm_key, recent_track_count).await;
+      *next_poll = Instant::now() + interval.get(playing.unwrap_or_default());
+    }
+  });
 
+  // top lists barely move, so they're refreshed far less often than the listening status
   tokio::spawn(async move {
+    let mut interval =
+      tokio::time::interval(Duration::from_secs(config.intervals.last_fm_top_lists));
+
     loop {
-      tokio::time::sleep(Duration::from_secs(10)).await;
-      perform_update().await
+      interval.tick().await;
+      for username in &top_usernames {
+        update_top_lists(username, last_fm_key).await;
+      }
     }
   });
 
@@ -142,20 +416,59 @@ struct RecentTracksBase {
 #[derive(Deserialize)]
 struct RecentTracks {
   track: Vec<Track>,
+  #[serde(rename = "@attr")]
+  attributes: RecentTracksAttributes,
+}
+
+#[derive(Deserialize)]
+struct RecentTracksAttributes {
+  #[serde(deserialize_with = "number_from_string")]
+  total: u64,
 }
 
-pub async fn update_currently_listening(username: &str, api_key: &str) {
-  let result = reqwest::get(format!("https://ws.audioscrobbler.com/2.0/?method=user.getrecenttracks&extended=1&user={username}&format=json&api_key={api_key}&limit=1"))
-  .and_then(Response::json::<RecentTracksBase>)
+fn number_from_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+  String::deserialize(deserializer)?
+    .parse()
+    .map_err(serde::de::Error::custom)
+}
+
+fn optional_number_from_string<'de, D: Deserializer<'de>>(
+  deserializer: D,
+) -> Result<Option<u64>, D::Error> {
+  number_from_string(deserializer).map(Some)
+}
+
+/// top tracks and albums embed their artist as an object, but we only want the name
+fn name_of<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+  #[derive(Deserialize)]
+  struct Named {
+    name: String,
+  }
+
+  Named::deserialize(deserializer).map(|named| named.name)
+}
+
+/// returns whether the user is currently listening to something, if we could find out
+pub async fn update_recent_tracks(username: &str, api_key: &str, count: usize) -> Option<bool> {
+  let result = api_request::<RecentTracksBase>(format!("https://ws.audioscrobbler.com/2.0/?method=user.getrecenttracks&extended=1&user={username}&format=json&api_key={api_key}&limit={count}"))
   .await;
+  record_result(username, &result);
   match result {
     Ok(response) => {
-      let currently_playing = response.recent_tracks.track.into_iter().find_map(|track| {
-        let Track::NowPlaying(now_playing) = track else {
-          return None;
-        };
-        Some(now_playing)
-      });
+      let mut currently_playing = None;
+      let mut recent_tracks = Vec::new();
+      for track in response.recent_tracks.track {
+        match track {
+          Track::NowPlaying(now_playing) => currently_playing = Some(now_playing),
+          Track::Recorded(recorded) => recent_tracks.push(recorded.into()),
+        }
+      }
+
+      let track_info = match &currently_playing {
+        Some(track) => fetch_track_info(username, api_key, track).await,
+        None => None,
+      };
+
       let mut users = PLAYING_TRACKS.write().unwrap();
       if let Some(user) = users.get_mut(username) {
         user.currently_playing = currently_playing.map(|track| {
@@ -166,6 +479,8 @@ pub async fn update_currently_listening(username: &str, api_key: &str) {
             .map(|track| track.start_time)
             .unwrap_or_else(|| Utc::now().round_subsecs(0));
 
+          let duration = track_info.as_ref().and_then(|info| info.duration);
+
           TypescriptTrack {
             start_time,
             name: track.name,
@@ -173,14 +488,226 @@ pub async fn update_currently_listening(username: &str, api_key: &str) {
             url: track.url,
             artist: track.artist,
             image: track.image,
+            loved: track_info.as_ref().map(|info| info.loved),
+            duration,
+            end_time: duration
+              .map(|duration| start_time + TimeDelta::milliseconds(duration as i64)),
+            user_playcount: track_info.as_ref().and_then(|info| info.user_playcount),
+            tags: track_info.map(|info| info.tags).unwrap_or_default(),
           }
         });
+        user.recent_tracks = recent_tracks;
+        user.scrobble_count = Some(response.recent_tracks.attributes.total);
       }
+
+      Some(users.get(username)?.currently_playing.is_some())
     }
     Err(error) => {
       tracing::error!(
         "failed to request listening status from last.fm for user {username}: {error}"
       );
+      None
+    }
+  }
+}
+
+#[derive(Deserialize)]
+struct TrackInfoBase {
+  track: TrackInfoResponse,
+}
+
+#[derive(Deserialize)]
+struct TrackInfoResponse {
+  #[serde(default, deserialize_with = "optional_number_from_string")]
+  duration: Option<u64>,
+  #[serde(default, deserialize_with = "optional_number_from_string")]
+  userplaycount: Option<u64>,
+  #[serde(default)]
+  userloved: String,
+  #[serde(default)]
+  toptags: TrackTags,
+}
+
+#[derive(Default, Deserialize)]
+struct TrackTags {
+  #[serde(default)]
+  tag: Vec<TrackTag>,
+}
+
+#[derive(Deserialize)]
+struct TrackTag {
+  name: String,
+}
+
+/// looks up the user's view of a track, caching it since it rarely changes mid-listen
+async fn fetch_track_info(
+  username: &str,
+  api_key: &str,
+  track: &NowPlayingTrack,
+) -> Option<TrackInfo> {
+  let key = (
+    username.to_owned(),
+    track.artist.name.clone(),
+    track.name.clone(),
+  );
+
+  if let Some(cached) = TRACK_INFO
+    .read()
+    .unwrap()
+    .get(&key)
+    .filter(|cached| Utc::now() - cached.fetched_at < TRACK_INFO_LIFETIME)
+  {
+    return cached.info.clone();
+  }
+
+  let url = Url::parse_with_params(
+    "https://ws.audioscrobbler.com/2.0/",
+    &[
+      ("method", "track.getInfo"),
+      ("artist", track.artist.name.as_str()),
+      ("track", track.name.as_str()),
+      ("username", username),
+      ("format", "json"),
+      ("api_key", api_key),
+    ],
+  )
+  .expect("last.fm url is valid");
+
+  let response = match api_request::<TrackInfoBase>(url).await {
+    Ok(response) => response.track,
+    // errors from last.fm itself, like an unknown track, won't change on the next poll
+    Err(FetchError::Api { code, message })
+      if code != ERROR_RATE_LIMITED && code != ERROR_SUSPENDED_KEY =>
+    {
+      tracing::warn!(
+        "last.fm has no track info for {} by {}: {message}",
+        track.name,
+        track.artist.name
+      );
+      cache_track_info(key, None);
+      return None;
+    }
+    Err(error) => {
+      tracing::error!("failed to request track info from last.fm for user {username}: {error}");
+      return None;
+    }
+  };
+
+  let info = TrackInfo {
+    loved: response.userloved == "1",
+    // last.fm reports 0 when it doesn't know the length
+    duration: response.duration.filter(|duration| *duration > 0),
+    user_playcount: response.userplaycount,
+    tags: response
+      .toptags
+      .tag
+      .into_iter()
+      .map(|tag| tag.name)
+      .collect(),
+  };
+
+  cache_track_info(key, Some(info.clone()));
+  Some(info)
+}
+
+fn cache_track_info(key: TrackKey, info: Option<TrackInfo>) {
+  let mut cache = TRACK_INFO.write().unwrap();
+  // entries are cheap to refetch, so just start over rather than tracking usage
+  if cache.len() >= TRACK_INFO_CAPACITY {
+    cache.clear();
+  }
+  cache.insert(
+    key,
+    CachedTrackInfo {
+      info,
+      fetched_at: Utc::now(),
+    },
+  );
+}
+
+#[derive(Deserialize)]
+struct TopArtistsBase {
+  #[serde(rename = "topartists")]
+  top_artists: TopArtists,
+}
+
+#[derive(Deserialize)]
+struct TopArtists {
+  artist: Vec<TopArtist>,
+}
+
+#[derive(Deserialize)]
+struct TopTracksBase {
+  #[serde(rename = "toptracks")]
+  top_tracks: TopTracks,
+}
+
+#[derive(Deserialize)]
+struct TopTracks {
+  track: Vec<TopTrack>,
+}
+
+#[derive(Deserialize)]
+struct TopAlbumsBase {
+  #[serde(rename = "topalbums")]
+  top_albums: TopAlbums,
+}
+
+#[derive(Deserialize)]
+struct TopAlbums {
+  album: Vec<TopAlbum>,
+}
+
+async fn fetch_top<T: DeserializeOwned>(
+  method: &str,
+  username: &str,
+  api_key: &str,
+  period: &str,
+) -> Option<T> {
+  let result = api_request::<T>(format!("https://ws.audioscrobbler.com/2.0/?method={method}&user={username}&period={period}&format=json&api_key={api_key}&limit=10"))
+  .await;
+  record_result(username, &result);
+
+  result
+    .inspect_err(|error| {
+      tracing::error!(
+        "failed to request {method} ({period}) from last.fm for user {username}: {error}"
+      )
+    })
+    .ok()
+}
+
+async fn fetch_top_list(username: &str, api_key: &str, period: &str) -> Option<TopList> {
+  let artists = fetch_top::<TopArtistsBase>("user.gettopartists", username, api_key, period);
+  let tracks = fetch_top::<TopTracksBase>("user.gettoptracks", username, api_key, period);
+  let albums = fetch_top::<TopAlbumsBase>("user.gettopalbums", username, api_key, period);
+  let (artists, tracks, albums) = futures::join!(artists, tracks, albums);
+
+  Some(TopList {
+    artists: artists?.top_artists.artist,
+    tracks: tracks?.top_tracks.track,
+    albums: albums?.top_albums.album,
+  })
+}
+
+pub async fn update_top_lists(username: &str, api_key: &str) {
+  let (seven_days, one_month, overall) = futures::join!(
+    fetch_top_list(username, api_key, "7day"),
+    fetch_top_list(username, api_key, "1month"),
+    fetch_top_list(username, api_key, "overall"),
+  );
+
+  let mut users = PLAYING_TRACKS.write().unwrap();
+  if let Some(user) = users.get_mut(username) {
+    // keep the previous lists around if a period failed to refresh
+    if let Some(seven_days) = seven_days {
+      user.top.seven_days = seven_days;
+    }
+    if let Some(one_month) = one_month {
+      user.top.one_month = one_month;
+    }
+    if let Some(overall) = overall {
+      user.top.overall = overall;
     }
   }
 }
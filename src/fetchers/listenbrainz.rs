@@ -0,0 +1,264 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+};
+
+use chrono::{DateTime, SubsecRound, TimeDelta, Utc};
+use lastfm::{artist::Artist, imageset::ImageSet};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use super::last_fm::{TopLists, TypescriptRecordedTrack, TypescriptTrack, UserInfo};
+use crate::config::{Config, ListenBrainzConfig};
+
+static LISTENS: LazyLock<RwLock<HashMap<String, UserInfo>>> = LazyLock::new(Default::default);
+
+pub fn fetch_listenbrainz_info(username: &str) -> Option<UserInfo> {
+  LISTENS.read().unwrap().get(username).cloned()
+}
+
+pub fn run(config: &'static Config) {
+  let users = config
+    .users
+    .values()
+    .filter_map(|config| config.listenbrainz.as_ref())
+    .collect::<Vec<_>>();
+
+  if users.is_empty() {
+    return;
+  }
+
+  *LISTENS.write().unwrap() = users
+    .into_iter()
+    .map(|user| {
+      (
+        user.username.clone(),
+        UserInfo {
+          username: user.username.clone(),
+          currently_playing: None,
+          recent_tracks: Vec::new(),
+          scrobble_count: None,
+          top: TopLists::default(),
+        },
+      )
+    })
+    .collect();
+
+  let recent_track_count = config.last_fm_recent_track_count;
+  for user in config.users.values() {
+    let Some(listenbrainz) = &user.listenbrainz else {
+      continue;
+    };
+    let interval = config.intervals.listenbrainz_for(&user.intervals);
+
+    tokio::spawn(async move {
+      loop {
+        let playing = update_listens(listenbrainz, recent_track_count).await;
+        tokio::time::sleep(interval.get(playing.unwrap_or_default())).await;
+      }
+    });
+  }
+
+  tracing::info!("started listenbrainz fetcher");
+}
+
+#[derive(Deserialize)]
+struct PayloadBase<T> {
+  payload: T,
+}
+
+#[derive(Deserialize)]
+struct Listens {
+  listens: Vec<Listen>,
+}
+
+#[derive(Deserialize)]
+struct ListenCount {
+  count: u64,
+}
+
+#[derive(Deserialize)]
+struct Listen {
+  listened_at: Option<i64>,
+  track_metadata: TrackMetadata,
+}
+
+#[derive(Deserialize)]
+struct TrackMetadata {
+  artist_name: String,
+  track_name: String,
+  release_name: Option<String>,
+  #[serde(default)]
+  additional_info: AdditionalInfo,
+}
+
+#[derive(Default, Deserialize)]
+struct AdditionalInfo {
+  duration_ms: Option<u64>,
+  origin_url: Option<String>,
+  release_mbid: Option<String>,
+  #[serde(default)]
+  tags: Vec<String>,
+}
+
+impl TrackMetadata {
+  fn artist(&self) -> Artist {
+    Artist {
+      image: ImageSet {
+        small: None,
+        medium: None,
+        large: None,
+        extralarge: None,
+      },
+      name: self.artist_name.clone(),
+      url: reqwest::Url::parse_with_params(
+        "https://listenbrainz.org/search/",
+        [
+          ("search_type", "artist"),
+          ("search_term", self.artist_name.as_str()),
+        ],
+      )
+      .map(String::from)
+      .unwrap_or_default(),
+    }
+  }
+
+  /// listenbrainz doesn't host artwork, so point at the cover art archive when we know the release
+  fn image(&self) -> ImageSet {
+    let cover = |size: u32| {
+      self
+        .additional_info
+        .release_mbid
+        .as_ref()
+        .map(|mbid| format!("https://coverartarchive.org/release/{mbid}/front-{size}"))
+    };
+
+    ImageSet {
+      small: cover(250),
+      medium: cover(250),
+      large: cover(500),
+      extralarge: cover(1200),
+    }
+  }
+
+  fn url(&self) -> String {
+    self.additional_info.origin_url.clone().unwrap_or_default()
+  }
+}
+
+async fn request<T: DeserializeOwned>(
+  user: &ListenBrainzConfig,
+  endpoint: &str,
+  query: &[(&str, &str)],
+) -> reqwest::Result<T> {
+  let client = reqwest::Client::new();
+  let mut request = client.get(&user.base_url).query(query);
+  if let Some(token) = &user.token {
+    request = request.header("Authorization", format!("Token {token}"));
+  }
+
+  // pushed as path segments, so they're encoded and a username can't reach into the path or query
+  let mut request = request.build()?;
+  if let Ok(mut segments) = request.url_mut().path_segments_mut() {
+    segments
+      .pop_if_empty()
+      .extend(["1", "user", &user.username, endpoint]);
+  }
+
+  client
+    .execute(request)
+    .await?
+    .error_for_status()?
+    .json::<PayloadBase<T>>()
+    .await
+    .map(|base| base.payload)
+}
+
+/// returns whether the user is currently listening to something, if we could find out
+async fn update_listens(user: &ListenBrainzConfig, count: usize) -> Option<bool> {
+  let username = &user.username;
+  let count = count.to_string();
+  let listens_query = [("count", count.as_str())];
+  let (playing_now, recent, listen_count) = futures::join!(
+    request::<Listens>(user, "playing-now", &[]),
+    request::<Listens>(user, "listens", &listens_query),
+    request::<ListenCount>(user, "listen-count", &[]),
+  );
+
+  let playing_now = match playing_now {
+    Ok(playing_now) => playing_now.listens.into_iter().next(),
+    Err(error) => {
+      tracing::error!(
+        "failed to request listening status from listenbrainz for user {username}: {error}"
+      );
+      return None;
+    }
+  };
+
+  let mut users = LISTENS.write().unwrap();
+  let info = users.get_mut(username)?;
+
+  info.currently_playing = playing_now.map(|listen| {
+    let track = listen.track_metadata;
+    let start_time = info
+      .currently_playing
+      .as_ref()
+      .filter(|previous| {
+        previous.name == track.track_name && previous.artist.name == track.artist_name
+      })
+      .map(|previous| previous.start_time)
+      .unwrap_or_else(|| Utc::now().round_subsecs(0));
+    let duration = track.additional_info.duration_ms;
+
+    TypescriptTrack {
+      artist: track.artist(),
+      image: track.image(),
+      url: track.url(),
+      album: track.release_name.clone().unwrap_or_default(),
+      name: track.track_name,
+      start_time,
+      loved: None,
+      duration,
+      end_time: duration.map(|duration| start_time + TimeDelta::milliseconds(duration as i64)),
+      user_playcount: None,
+      tags: track.additional_info.tags,
+    }
+  });
+
+  match recent {
+    Ok(recent) => {
+      info.recent_tracks = recent
+        .listens
+        .into_iter()
+        .filter_map(|listen| {
+          let date = DateTime::from_timestamp(listen.listened_at?, 0)?;
+          let track = listen.track_metadata;
+
+          Some(TypescriptRecordedTrack {
+            artist: track.artist(),
+            image: track.image(),
+            url: track.url(),
+            album: track.release_name.clone().unwrap_or_default(),
+            name: track.track_name,
+            date,
+          })
+        })
+        .collect()
+    }
+    Err(error) => {
+      tracing::error!(
+        "failed to request recent listens from listenbrainz for user {username}: {error}"
+      );
+    }
+  }
+
+  match listen_count {
+    Ok(listen_count) => info.scrobble_count = Some(listen_count.count),
+    Err(error) => {
+      tracing::error!(
+        "failed to request listen count from listenbrainz for user {username}: {error}"
+      );
+    }
+  }
+
+  Some(info.currently_playing.is_some())
+}
@@ -0,0 +1,366 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+  time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::config::{Config, has_scope};
+
+/// jellyfin measures time in ticks of 100 nanoseconds
+const TICKS_PER_SECOND: u64 = 10_000_000;
+/// a server that's been unreachable this many polls in a row has its sessions dropped
+const STALE_AFTER_FAILURES: u32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, TS)]
+#[ts(rename = "MediaServer")]
+pub enum Server {
+  #[serde(rename = "jellyfin")]
+  Jellyfin,
+  #[serde(rename = "plex")]
+  Plex,
+}
+
+#[derive(Clone, Copy, Serialize, TS)]
+pub enum MediaKind {
+  #[serde(rename = "movie")]
+  Movie,
+  #[serde(rename = "episode")]
+  Episode,
+  #[serde(rename = "track")]
+  Track,
+  #[serde(rename = "other")]
+  Other,
+}
+
+impl MediaKind {
+  fn from_name(name: &str) -> Self {
+    match name.to_ascii_lowercase().as_str() {
+      "movie" => MediaKind::Movie,
+      "episode" => MediaKind::Episode,
+      "audio" | "track" => MediaKind::Track,
+      _ => MediaKind::Other,
+    }
+  }
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct MediaSession {
+  server: Server,
+  kind: MediaKind,
+  title: String,
+  series: Option<String>,
+  season: Option<u32>,
+  episode: Option<u32>,
+  position_seconds: Option<u64>,
+  duration_seconds: Option<u64>,
+  /// how far through the item they are, from 0 to 1
+  progress: Option<f64>,
+  paused: bool,
+  poster_url: Option<String>,
+}
+
+impl MediaSession {
+  /// paused sessions are often just left open, so they don't count
+  pub fn activity(&self) -> Option<String> {
+    if self.paused {
+      return None;
+    }
+
+    Some(match (&self.series, self.season, self.episode) {
+      (Some(series), Some(season), Some(episode)) => {
+        format!("watching {series} S{season:02}E{episode:02}")
+      }
+      (Some(series), ..) => format!("watching {series}: {}", self.title),
+      _ if matches!(self.kind, MediaKind::Track) => format!("listening to {}", self.title),
+      _ => format!("watching {}", self.title),
+    })
+  }
+}
+
+/// kept per server, so one going down doesn't freeze or wipe what the other reports
+static SESSIONS: LazyLock<RwLock<HashMap<Server, HashMap<String, MediaSession>>>> =
+  LazyLock::new(Default::default);
+
+pub fn get_user_info(username: &str, auth_scopes: &[String]) -> Option<MediaSession> {
+  if !has_scope(auth_scopes, "media.watching") {
+    return None;
+  }
+
+  let sessions = SESSIONS.read().unwrap();
+  [Server::Jellyfin, Server::Plex]
+    .iter()
+    .find_map(|server| sessions.get(server)?.get(username))
+    .cloned()
+}
+
+pub fn run(config: &'static Config) {
+  let jellyfin = config
+    .jellyfin_server
+    .as_ref()
+    .zip(config.jellyfin_api_key.as_ref());
+  let plex = config.plex_server.as_ref().zip(config.plex_token.as_ref());
+  if jellyfin.is_none() && plex.is_none() {
+    return;
+  }
+
+  // both servers identify users their own way, so map those back to our usernames
+  let jellyfin_users = config
+    .users
+    .iter()
+    .filter_map(|(username, user)| Some((user.jellyfin_user_id.as_deref()?, username.as_str())))
+    .collect::<HashMap<_, _>>();
+  let plex_users = config
+    .users
+    .iter()
+    .filter_map(|(username, user)| Some((user.plex_username.as_deref()?, username.as_str())))
+    .collect::<HashMap<_, _>>();
+
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.media_server));
+    let mut failures = HashMap::<Server, u32>::new();
+
+    loop {
+      interval.tick().await;
+
+      let (jellyfin_sessions, plex_sessions) = futures::join!(
+        async {
+          match jellyfin {
+            Some((server, api_key)) => {
+              fetch_jellyfin_sessions(server, api_key, &jellyfin_users).await
+            }
+            None => Some(Vec::new()),
+          }
+        },
+        async {
+          match plex {
+            Some((server, token)) => fetch_plex_sessions(server, token, &plex_users).await,
+            None => Some(Vec::new()),
+          }
+        },
+      );
+
+      let mut stored = SESSIONS.write().unwrap();
+      for (server, sessions) in [
+        (Server::Jellyfin, jellyfin_sessions),
+        (Server::Plex, plex_sessions),
+      ] {
+        match sessions {
+          Some(sessions) => {
+            failures.remove(&server);
+            stored.insert(
+              server,
+              sessions
+                .into_iter()
+                .map(|(username, session)| (username.to_owned(), session))
+                .collect(),
+            );
+          }
+          // keep the last known sessions through a hiccup, but not once the server seems gone
+          None => {
+            let failed = failures.entry(server).or_default();
+            *failed += 1;
+            if *failed >= STALE_AFTER_FAILURES {
+              stored.remove(&server);
+            }
+          }
+        }
+      }
+    }
+  });
+
+  tracing::info!("started media server fetcher");
+}
+
+fn progress(position: Option<u64>, duration: Option<u64>) -> Option<f64> {
+  let duration = duration.filter(|duration| *duration > 0)?;
+  Some((position? as f64 / duration as f64).clamp(0.0, 1.0))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinSession {
+  user_id: Option<String>,
+  now_playing_item: Option<JellyfinItem>,
+  play_state: Option<JellyfinPlayState>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinItem {
+  id: String,
+  name: String,
+  #[serde(rename = "Type")]
+  kind: String,
+  series_name: Option<String>,
+  series_id: Option<String>,
+  parent_index_number: Option<u32>,
+  index_number: Option<u32>,
+  run_time_ticks: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinPlayState {
+  position_ticks: Option<u64>,
+  #[serde(default)]
+  is_paused: bool,
+}
+
+async fn fetch_jellyfin_sessions<'a>(
+  server: &str,
+  api_key: &str,
+  users: &HashMap<&str, &'a str>,
+) -> Option<Vec<(&'a str, MediaSession)>> {
+  let sessions = reqwest::Client::new()
+    .get(format!("{server}/Sessions?activeWithinSeconds=60"))
+    .header("X-Emby-Token", api_key)
+    .send()
+    .await
+    .and_then(|response| response.error_for_status())
+    .inspect_err(|error| tracing::error!("failed to request jellyfin sessions: {error}"))
+    .ok()?
+    .json::<Vec<JellyfinSession>>()
+    .await
+    .inspect_err(|error| tracing::error!("failed to parse jellyfin sessions: {error}"))
+    .ok()?;
+
+  Some(
+    sessions
+      .into_iter()
+      .filter_map(|session| {
+        let username = *users.get(session.user_id?.as_str())?;
+        let item = session.now_playing_item?;
+        let position = session
+          .play_state
+          .as_ref()
+          .and_then(|play_state| play_state.position_ticks)
+          .map(|ticks| ticks / TICKS_PER_SECOND);
+        let duration = item.run_time_ticks.map(|ticks| ticks / TICKS_PER_SECOND);
+        // episodes look better with the series poster than a screenshot
+        let poster_id = item.series_id.as_ref().unwrap_or(&item.id);
+
+        Some((
+          username,
+          MediaSession {
+            server: Server::Jellyfin,
+            kind: MediaKind::from_name(&item.kind),
+            poster_url: Some(format!("{server}/Items/{poster_id}/Images/Primary")),
+            title: item.name,
+            series: item.series_name,
+            season: item.parent_index_number,
+            episode: item.index_number,
+            position_seconds: position,
+            duration_seconds: duration,
+            progress: progress(position, duration),
+            paused: session
+              .play_state
+              .is_some_and(|play_state| play_state.is_paused),
+          },
+        ))
+      })
+      .collect(),
+  )
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexBase {
+  media_container: PlexContainer,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexContainer {
+  #[serde(default)]
+  metadata: Vec<PlexMetadata>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlexMetadata {
+  #[serde(rename = "type")]
+  kind: String,
+  title: String,
+  grandparent_title: Option<String>,
+  parent_index: Option<u32>,
+  index: Option<u32>,
+  /// in milliseconds
+  duration: Option<u64>,
+  /// in milliseconds
+  view_offset: Option<u64>,
+  #[serde(rename = "User")]
+  user: Option<PlexUser>,
+  #[serde(rename = "Player")]
+  player: Option<PlexPlayer>,
+}
+
+#[derive(Deserialize)]
+struct PlexUser {
+  title: String,
+}
+
+#[derive(Deserialize)]
+struct PlexPlayer {
+  state: String,
+}
+
+async fn fetch_plex_sessions<'a>(
+  server: &str,
+  token: &str,
+  users: &HashMap<&str, &'a str>,
+) -> Option<Vec<(&'a str, MediaSession)>> {
+  let sessions = reqwest::Client::new()
+    .get(format!("{server}/status/sessions"))
+    .header("X-Plex-Token", token)
+    .header("Accept", "application/json")
+    .send()
+    .await
+    .and_then(|response| response.error_for_status())
+    .inspect_err(|error| tracing::error!("failed to request plex sessions: {error}"))
+    .ok()?
+    .json::<PlexBase>()
+    .await
+    .inspect_err(|error| tracing::error!("failed to parse plex sessions: {error}"))
+    .ok()?;
+
+  Some(
+    sessions
+      .media_container
+      .metadata
+      .into_iter()
+      .filter_map(|metadata| {
+        let username = *users.get(metadata.user?.title.as_str())?;
+        let kind = MediaKind::from_name(&metadata.kind);
+        let position = metadata.view_offset.map(|offset| offset / 1000);
+        let duration = metadata.duration.map(|duration| duration / 1000);
+        // plex uses the grandparent for both the series of an episode and the artist of a track
+        let series = matches!(kind, MediaKind::Episode)
+          .then_some(metadata.grandparent_title)
+          .flatten();
+
+        Some((
+          username,
+          MediaSession {
+            server: Server::Plex,
+            kind,
+            title: metadata.title,
+            series,
+            season: metadata.parent_index,
+            episode: metadata.index,
+            position_seconds: position,
+            duration_seconds: duration,
+            progress: progress(position, duration),
+            paused: metadata
+              .player
+              .is_some_and(|player| player.state == "paused"),
+            // plex artwork can't be fetched without handing out the server token
+            poster_url: None,
+          },
+        ))
+      })
+      .collect(),
+  )
+}
@@ -1,4 +1,16 @@
+pub mod bluesky;
+pub mod custom;
 pub mod discord;
+pub mod fediverse;
+pub mod geocode;
+pub mod github;
+pub mod home_assistant;
+pub mod icloud;
+pub mod json_source;
 pub mod last_fm;
+pub mod listenbrainz;
+pub mod media_server;
+pub mod owntracks;
 pub mod steam;
-pub mod icloud;
+pub mod twitch;
+pub mod wakatime;
@@ -0,0 +1,152 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+};
+
+use serde::Deserialize;
+
+use crate::{
+  config::Config,
+  fetchers::{
+    geocode::{REGEOCODE_DISTANCE, distance, reverse_geocode},
+    icloud::{DeviceInfo, Location},
+  },
+};
+
+/// what the owntracks apps post in http mode, anything else is ignored
+#[derive(Deserialize)]
+#[serde(tag = "_type", rename_all = "lowercase")]
+pub enum Message {
+  Location {
+    lat: f64,
+    lon: f64,
+    /// the regions set up on the device that they're currently inside
+    #[serde(default)]
+    inregions: Vec<String>,
+  },
+  Waypoint(Waypoint),
+  Waypoints {
+    waypoints: Vec<Waypoint>,
+  },
+  #[serde(other)]
+  Other,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Waypoint {
+  desc: String,
+  lat: f64,
+  lon: f64,
+  /// in meters
+  rad: f64,
+}
+
+static USERS: LazyLock<RwLock<HashMap<String, DeviceInfo>>> = LazyLock::new(Default::default);
+/// regions reported by the device itself, on top of the ones in the config
+static WAYPOINTS: LazyLock<RwLock<HashMap<String, Vec<Waypoint>>>> =
+  LazyLock::new(Default::default);
+
+pub fn get_location(username: &str, auth_scopes: &[String]) -> Option<Location> {
+  USERS
+    .read()
+    .unwrap()
+    .get(username)
+    .map(|location| location.to_location(auth_scopes))
+}
+
+pub async fn handle_message(config: &'static Config, username: &str, message: Message) {
+  match message {
+    Message::Location {
+      lat,
+      lon,
+      inregions,
+    } => update_location(config, username, lat, lon, inregions).await,
+    Message::Waypoint(waypoint) => {
+      let mut waypoints = WAYPOINTS.write().unwrap();
+      let waypoints = waypoints.entry(username.to_owned()).or_default();
+      waypoints.retain(|existing| existing.desc != waypoint.desc);
+      waypoints.push(waypoint);
+    }
+    Message::Waypoints { waypoints } => {
+      WAYPOINTS
+        .write()
+        .unwrap()
+        .insert(username.to_owned(), waypoints);
+    }
+    Message::Other => {}
+  }
+}
+
+async fn update_location(
+  config: &'static Config,
+  username: &str,
+  latitude: f64,
+  longitude: f64,
+  inregions: Vec<String>,
+) {
+  let regions = config
+    .users
+    .get(username)
+    .and_then(|user| user.owntracks.as_ref())
+    .map(|owntracks| owntracks.regions.as_slice())
+    .unwrap_or_default();
+
+  let zone = inregions.into_iter().next().or_else(|| {
+    let waypoints = WAYPOINTS.read().unwrap();
+    let waypoints = waypoints
+      .get(username)
+      .map(Vec::as_slice)
+      .unwrap_or_default();
+
+    regions
+      .iter()
+      .map(|region| {
+        (
+          &region.name,
+          region.latitude,
+          region.longitude,
+          region.radius,
+        )
+      })
+      .chain(
+        waypoints
+          .iter()
+          .map(|waypoint| (&waypoint.desc, waypoint.lat, waypoint.lon, waypoint.rad)),
+      )
+      .find(|(_, region_latitude, region_longitude, radius)| {
+        distance((latitude, longitude), (*region_latitude, *region_longitude)) <= *radius
+      })
+      .map(|(name, ..)| name.clone())
+  });
+
+  // nominatim asks for no more than a request a second, so only look up places that are new
+  let previous = USERS.read().unwrap().get(username).and_then(|previous| {
+    (distance(
+      (previous.latitude, previous.longitude),
+      (latitude, longitude),
+    ) < REGEOCODE_DISTANCE)
+      .then(|| (previous.country.clone(), previous.locality.clone()))
+  });
+
+  let address = match previous {
+    Some(address) => address,
+    None => match reverse_geocode(config, latitude, longitude).await {
+      Ok(address) => address,
+      Err(error) => {
+        tracing::error!("failed to reverse geocode owntracks location for {username}: {error}");
+        return;
+      }
+    },
+  };
+
+  USERS.write().unwrap().insert(
+    username.to_owned(),
+    DeviceInfo {
+      country: address.0,
+      locality: address.1,
+      latitude,
+      longitude,
+      zone,
+    },
+  );
+}
@@ -1,22 +1,95 @@
 use std::{
-  collections::HashMap,
-  sync::{LazyLock, RwLock},
+  collections::{HashMap, HashSet},
+  path::{Path, PathBuf},
+  sync::{LazyLock, Mutex, OnceLock, RwLock},
   time::Duration,
 };
 
+use chrono::{DateTime, Utc};
 use futures::TryFutureExt;
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "embedded-app-list")]
 use serenity::json::Value;
-use steam_rs::{Steam, steam_id::SteamId};
+use steam_rs::steam_id::SteamId;
+use tokio::{
+  sync::mpsc::{UnboundedSender, unbounded_channel},
+  time::Instant,
+};
+use tracing::warn;
 use ts_rs::TS;
 
-use crate::config::Config;
+use crate::config::{Config, PollInterval};
+
+#[derive(Clone, Copy, PartialEq, Serialize, TS)]
+pub enum SteamPersonaState {
+  #[serde(rename = "offline")]
+  Offline,
+  #[serde(rename = "online")]
+  Online,
+  #[serde(rename = "busy")]
+  Busy,
+  #[serde(rename = "away")]
+  Away,
+  #[serde(rename = "snooze")]
+  Snooze,
+  #[serde(rename = "looking_to_trade")]
+  LookingToTrade,
+  #[serde(rename = "looking_to_play")]
+  LookingToPlay,
+}
+
+impl<'de> Deserialize<'de> for SteamPersonaState {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    Ok(match u8::deserialize(deserializer)? {
+      1 => SteamPersonaState::Online,
+      2 => SteamPersonaState::Busy,
+      3 => SteamPersonaState::Away,
+      4 => SteamPersonaState::Snooze,
+      5 => SteamPersonaState::LookingToTrade,
+      6 => SteamPersonaState::LookingToPlay,
+      // steam documents 0 as offline, and anything else is as good as unknown
+      _ => SteamPersonaState::Offline,
+    })
+  }
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct SteamAvatar {
+  small: String,
+  medium: String,
+  full: String,
+}
 
 #[derive(Clone, Serialize, TS)]
 pub struct SteamUserInfo {
   steam_id: String,
   persona_name: String,
+  persona_state: SteamPersonaState,
+  last_logoff: Option<DateTime<Utc>>,
+  avatar: SteamAvatar,
+  profile_url: String,
   game: Option<SteamGameInfo>,
+  recently_played: Vec<SteamPlayedGame>,
+  library: Option<SteamLibrary>,
+}
+
+/// playtimes are in minutes, as steam reports them
+#[derive(Clone, Serialize, TS)]
+pub struct SteamPlayedGame {
+  appid: u64,
+  name: String,
+  playtime_two_weeks: Option<u64>,
+  playtime_forever: u64,
+  icon_url: Option<String>,
+  header_url: String,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct SteamLibrary {
+  game_count: u64,
+  /// in minutes
+  total_playtime: u64,
+  most_played: Vec<SteamPlayedGame>,
 }
 
 #[derive(Clone, Serialize, TS)]
@@ -24,27 +97,473 @@ pub struct SteamGameInfo {
   appid: u64,
   name: String,
   info_url: String,
+  /// filled in once the store page has been fetched
+  details: Option<SteamAppDetails>,
+  /// missing for games without achievements, or while they're still being fetched
+  achievements: Option<SteamAchievementProgress>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct SteamAchievementProgress {
+  unlocked: usize,
+  total: usize,
+  recent: Vec<SteamAchievement>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct SteamAchievement {
+  name: String,
+  description: Option<String>,
+  icon_url: String,
+  unlocked_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, Deserialize, TS)]
+pub struct SteamAppDetails {
+  header_image: String,
+  short_description: String,
+  genres: Vec<String>,
+  developers: Vec<String>,
+  release_date: Option<String>,
 }
 
 static USER_INFO: LazyLock<RwLock<HashMap<u64, SteamUserInfo>>> = LazyLock::new(Default::default);
 
+#[derive(Default)]
+struct GameStats {
+  recently_played: Vec<SteamPlayedGame>,
+  library: Option<SteamLibrary>,
+}
+
+/// refreshed separately from the summaries, since playtime changes far slower than presence
+static GAME_STATS: LazyLock<RwLock<HashMap<u64, GameStats>>> = LazyLock::new(Default::default);
+const MOST_PLAYED_COUNT: usize = 10;
+
+impl SteamUserInfo {
+  pub fn activity(&self) -> Option<String> {
+    Some(format!("playing {}", self.game.as_ref()?.name))
+  }
+}
+
 pub fn get_user_info(steam_id: SteamId) -> Option<SteamUserInfo> {
-  USER_INFO.read().unwrap().get(&steam_id.into_u64()).cloned()
+  let mut info = USER_INFO
+    .read()
+    .unwrap()
+    .get(&steam_id.into_u64())
+    .cloned()?;
+  if let Some(stats) = GAME_STATS.read().unwrap().get(&steam_id.into_u64()) {
+    info.recently_played = stats.recently_played.clone();
+    info.library = stats.library.clone();
+  }
+  if let Some(game) = &mut info.game {
+    game.details = APP_DETAILS.read().unwrap().get(&game.appid).cloned();
+    game.achievements = ACHIEVEMENTS
+      .read()
+      .unwrap()
+      .get(&steam_id.into_u64())
+      .filter(|(appid, _)| *appid == game.appid)
+      .map(|(_, progress)| progress.clone());
+  }
+
+  Some(info)
 }
 
-static GAME_NAMES: LazyLock<RwLock<HashMap<u64, String>>> = LazyLock::new(|| {
-  RwLock::new(
-    convert_game_list(serde_json::from_str(include_str!("./initial_steam_games.json")).unwrap())
-      .expect("failed to parse steam games"),
-  )
-});
+static APP_DETAILS: LazyLock<RwLock<HashMap<u64, SteamAppDetails>>> =
+  LazyLock::new(Default::default);
+/// apps the store didn't know about (like non-steam games) and when it said so, kept out of the
+/// cache file so they get asked about again after a while
+static UNKNOWN_APPS: LazyLock<RwLock<HashMap<u64, Instant>>> = LazyLock::new(Default::default);
+const UNKNOWN_APP_RETRY: Duration = Duration::from_secs(24 * 60 * 60);
+static PENDING_APP_DETAILS: LazyLock<Mutex<HashSet<u64>>> = LazyLock::new(Default::default);
+
+#[derive(Deserialize)]
+struct AppDetailsEntry {
+  success: bool,
+  data: Option<AppDetailsData>,
+}
+
+#[derive(Deserialize)]
+struct AppDetailsData {
+  name: String,
+  header_image: String,
+  #[serde(default)]
+  short_description: String,
+  #[serde(default)]
+  genres: Vec<Genre>,
+  #[serde(default)]
+  developers: Vec<String>,
+  release_date: Option<ReleaseDate>,
+}
+
+#[derive(Deserialize)]
+struct Genre {
+  description: String,
+}
+
+#[derive(Deserialize)]
+struct ReleaseDate {
+  date: String,
+}
+
+fn app_details_path(cache_directory: &Path) -> PathBuf {
+  cache_directory.join("steam_app_details.json")
+}
+
+fn load_app_details(cache_directory: &Path) {
+  let Ok(contents) = std::fs::read_to_string(app_details_path(cache_directory)) else {
+    return;
+  };
+
+  // older caches also kept unknown apps, as nulls
+  match serde_json::from_str::<HashMap<u64, Option<SteamAppDetails>>>(&contents) {
+    Ok(app_details) => {
+      *APP_DETAILS.write().unwrap() = app_details
+        .into_iter()
+        .filter_map(|(appid, details)| Some((appid, details?)))
+        .collect()
+    }
+    Err(error) => warn!("ignoring unreadable steam app details cache: {error}"),
+  }
+}
+
+/// the outer `None` means the request failed and is worth retrying later
+async fn fetch_app_details(appid: u64) -> Option<Option<SteamAppDetails>> {
+  let mut response = reqwest::get(format!(
+    "https://store.steampowered.com/api/appdetails?appids={appid}"
+  ))
+  .and_then(|response| response.json::<HashMap<String, AppDetailsEntry>>())
+  .await
+  .inspect_err(|error| tracing::error!("failed to fetch app details for {appid}: {error:?}"))
+  .ok()?;
+
+  let entry = response.remove(&appid.to_string())?;
+  Some(entry.data.filter(|_| entry.success).map(|data| {
+    // also covers apps the app list hasn't caught up with yet
+    GAME_NAMES
+      .write()
+      .unwrap()
+      .entry(appid)
+      .or_insert(data.name);
+
+    SteamAppDetails {
+      header_image: data.header_image,
+      short_description: data.short_description,
+      genres: data
+        .genres
+        .into_iter()
+        .map(|genre| genre.description)
+        .collect(),
+      developers: data.developers,
+      release_date: data.release_date.map(|release_date| release_date.date),
+    }
+  }))
+}
+
+/// whether the store told us it doesn't know an app recently enough not to ask again
+fn is_unknown_app(appid: u64) -> bool {
+  UNKNOWN_APPS
+    .read()
+    .unwrap()
+    .get(&appid)
+    .is_some_and(|checked_at| checked_at.elapsed() < UNKNOWN_APP_RETRY)
+}
+
+/// fetches and caches an app's store details in the background, unless we already have them
+fn request_app_details(appid: u64) {
+  if APP_DETAILS.read().unwrap().contains_key(&appid)
+    || is_unknown_app(appid)
+    || !PENDING_APP_DETAILS.lock().unwrap().insert(appid)
+  {
+    return;
+  }
+
+  tokio::spawn(async move {
+    let details = fetch_app_details(appid).await;
+    PENDING_APP_DETAILS.lock().unwrap().remove(&appid);
+    match details {
+      Some(Some(details)) => {
+        UNKNOWN_APPS.write().unwrap().remove(&appid);
+        APP_DETAILS.write().unwrap().insert(appid, details);
+        save_cache(CacheFile::AppDetails);
+      }
+      Some(None) => {
+        UNKNOWN_APPS.write().unwrap().insert(appid, Instant::now());
+      }
+      None => {}
+    }
+  });
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CacheFile {
+  AppDetails,
+  AppList,
+}
+
+/// every cache write goes through one task, so an older snapshot can never land after a newer one
+static CACHE_WRITES: OnceLock<UnboundedSender<CacheFile>> = OnceLock::new();
+
+fn save_cache(file: CacheFile) {
+  if let Some(sender) = CACHE_WRITES.get() {
+    let _ = sender.send(file);
+  }
+}
+
+fn start_cache_writer(cache_directory: &'static Path) {
+  let (sender, mut receiver) = unbounded_channel();
+  if CACHE_WRITES.set(sender).is_err() {
+    return;
+  }
+
+  tokio::spawn(async move {
+    while let Some(file) = receiver.recv().await {
+      // files are serialized when they're written, so requests that piled up can share a write
+      let mut files = vec![file];
+      while let Ok(file) = receiver.try_recv() {
+        if !files.contains(&file) {
+          files.push(file);
+        }
+      }
+
+      for file in files {
+        let (path, contents) = match file {
+          CacheFile::AppDetails => (
+            app_details_path(cache_directory),
+            serde_json::to_string(&*APP_DETAILS.read().unwrap()),
+          ),
+          CacheFile::AppList => (
+            app_list_path(cache_directory),
+            serde_json::to_string(&AppListCache {
+              synced_at: *APP_LIST_SYNCED_AT.read().unwrap(),
+              names: GAME_NAMES.read().unwrap().clone(),
+            }),
+          ),
+        };
+
+        write_cache(path, contents.expect("steam caches are serializable")).await;
+      }
+    }
+  });
+}
+
+async fn write_cache(path: PathBuf, contents: String) {
+  // written beside the real file and renamed over it, so a crash can't leave half a cache behind
+  let written = tokio::task::spawn_blocking(move || {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension("json.tmp");
+    std::fs::write(&temporary, contents)?;
+    std::fs::rename(&temporary, &path)
+  })
+  .await;
+
+  if let Ok(Err(error)) = written {
+    warn!("failed to write steam cache: {error}");
+  }
+}
+
+/// the achievement progress of each player in the game they were last seen playing, keyed by steam id
+static ACHIEVEMENTS: LazyLock<RwLock<HashMap<u64, (u64, SteamAchievementProgress)>>> =
+  LazyLock::new(Default::default);
+/// `None` marks games without achievements
+static ACHIEVEMENT_SCHEMAS: LazyLock<RwLock<HashMap<u64, Option<AchievementSchema>>>> =
+  LazyLock::new(Default::default);
+/// when each schema last failed to fetch, so it's retried after a while rather than every poll
+static FAILED_SCHEMAS: LazyLock<RwLock<HashMap<u64, Instant>>> = LazyLock::new(Default::default);
+const SCHEMA_RETRY: Duration = Duration::from_secs(10 * 60);
+/// players whose achievements are being fetched, so slow requests don't pile up across polls
+static PENDING_ACHIEVEMENTS: LazyLock<Mutex<HashSet<u64>>> = LazyLock::new(Default::default);
+const RECENT_ACHIEVEMENT_COUNT: usize = 5;
+
+#[derive(Deserialize)]
+struct SchemaBase {
+  #[serde(default)]
+  game: Schema,
+}
+
+#[derive(Default, Deserialize)]
+struct Schema {
+  #[serde(rename = "availableGameStats")]
+  available_game_stats: Option<AvailableGameStats>,
+}
+
+#[derive(Deserialize)]
+struct AvailableGameStats {
+  #[serde(default)]
+  achievements: Vec<SchemaAchievement>,
+}
+
+#[derive(Clone, Deserialize)]
+struct SchemaAchievement {
+  name: String,
+  #[serde(rename = "displayName")]
+  display_name: String,
+  description: Option<String>,
+  icon: String,
+}
+
+#[derive(Deserialize)]
+struct PlayerAchievementsBase {
+  #[serde(rename = "playerstats")]
+  player_stats: PlayerStats,
+}
+
+#[derive(Deserialize)]
+struct PlayerStats {
+  success: bool,
+  #[serde(default)]
+  achievements: Vec<PlayerAchievement>,
+}
+
+#[derive(Deserialize)]
+struct PlayerAchievement {
+  #[serde(rename = "apiname")]
+  api_name: String,
+  achieved: u8,
+  #[serde(rename = "unlocktime")]
+  unlock_time: i64,
+}
+
+/// keyed by the achievement's api name
+type AchievementSchema = HashMap<String, SchemaAchievement>;
+
+async fn fetch_achievement_schema(api_key: &str, appid: u64) -> Option<AchievementSchema> {
+  if let Some(schema) = ACHIEVEMENT_SCHEMAS.read().unwrap().get(&appid) {
+    return schema.clone();
+  }
+
+  // the store not knowing an app (like a non-steam shortcut) means there's no schema to find either
+  if is_unknown_app(appid) {
+    return None;
+  }
+
+  if FAILED_SCHEMAS
+    .read()
+    .unwrap()
+    .get(&appid)
+    .is_some_and(|failed_at| failed_at.elapsed() < SCHEMA_RETRY)
+  {
+    return None;
+  }
+
+  let schema = match request_achievement_schema(api_key, appid).await {
+    Ok(schema) => schema,
+    Err(error) => {
+      tracing::error!("failed to fetch achievement schema for {appid}: {error:?}");
+      FAILED_SCHEMAS
+        .write()
+        .unwrap()
+        .insert(appid, Instant::now());
+      return None;
+    }
+  };
+
+  // only a schema that really came back is remembered, games without stats included
+  let achievements = schema
+    .game
+    .available_game_stats
+    .map(|stats| stats.achievements)
+    .filter(|achievements| !achievements.is_empty())
+    .map(|achievements| {
+      achievements
+        .into_iter()
+        .map(|achievement| (achievement.name.clone(), achievement))
+        .collect::<HashMap<_, _>>()
+    });
+
+  ACHIEVEMENT_SCHEMAS
+    .write()
+    .unwrap()
+    .insert(appid, achievements.clone());
+  achievements
+}
+
+async fn request_achievement_schema(api_key: &str, appid: u64) -> reqwest::Result<SchemaBase> {
+  reqwest::get(format!(
+    "https://api.steampowered.com/ISteamUserStats/GetSchemaForGame/v2/?key={api_key}&appid={appid}"
+  ))
+  .and_then(|response| async move { response.error_for_status() })
+  .and_then(|response| response.json())
+  .await
+  // the api key is part of the url, which errors would otherwise print
+  .map_err(reqwest::Error::without_url)
+}
+
+async fn update_achievements(api_key: &str, steam_id: u64, appid: u64) {
+  let Some(schema) = fetch_achievement_schema(api_key, appid).await else {
+    ACHIEVEMENTS.write().unwrap().remove(&steam_id);
+    return;
+  };
+
+  // private profiles and games without stats both come back as errors, which we treat the same
+  let Some(player_stats) = reqwest::get(format!(
+    "https://api.steampowered.com/ISteamUserStats/GetPlayerAchievements/v1/?key={api_key}&steamid={steam_id}&appid={appid}"
+  ))
+  .and_then(|response| response.json::<PlayerAchievementsBase>())
+  .await
+  .map(|base| base.player_stats)
+  .ok()
+  .filter(|player_stats| player_stats.success)
+  else {
+    ACHIEVEMENTS.write().unwrap().remove(&steam_id);
+    return;
+  };
+
+  let mut unlocked = player_stats
+    .achievements
+    .into_iter()
+    .filter(|achievement| achievement.achieved == 1)
+    .collect::<Vec<_>>();
+  unlocked.sort_by_key(|achievement| std::cmp::Reverse(achievement.unlock_time));
+
+  let progress = SteamAchievementProgress {
+    unlocked: unlocked.len(),
+    total: schema.len(),
+    recent: unlocked
+      .iter()
+      .filter_map(|achievement| {
+        let schema = schema.get(&achievement.api_name)?;
+        Some(SteamAchievement {
+          name: schema.display_name.clone(),
+          description: schema.description.clone(),
+          icon_url: schema.icon.clone(),
+          unlocked_at: DateTime::from_timestamp(achievement.unlock_time, 0)?,
+        })
+      })
+      .take(RECENT_ACHIEVEMENT_COUNT)
+      .collect(),
+  };
 
+  ACHIEVEMENTS
+    .write()
+    .unwrap()
+    .insert(steam_id, (appid, progress));
+}
+
+static GAME_NAMES: LazyLock<RwLock<HashMap<u64, String>>> =
+  LazyLock::new(|| RwLock::new(initial_game_names()));
+/// unix time of the last app list sync, so the next one only asks for what changed since
+static APP_LIST_SYNCED_AT: RwLock<Option<i64>> = RwLock::new(None);
+
+#[cfg(feature = "embedded-app-list")]
+fn initial_game_names() -> HashMap<u64, String> {
+  convert_game_list(serde_json::from_str(include_str!("./initial_steam_games.json")).unwrap())
+    .expect("failed to parse steam games")
+}
+
+#[cfg(not(feature = "embedded-app-list"))]
+fn initial_game_names() -> HashMap<u64, String> {
+  HashMap::new()
+}
+
+#[cfg(feature = "embedded-app-list")]
 fn convert_game_list(value: Value) -> Option<HashMap<u64, String>> {
   let games = value.get("applist")?.get("apps")?.as_array()?;
 
   Some(
     games
-      .into_iter()
+      .iter()
       .filter_map(|game_entry| {
         let id = game_entry.get("appid")?.as_u64()?;
         let name = game_entry.get("name")?.as_str()?;
@@ -55,20 +574,254 @@ fn convert_game_list(value: Value) -> Option<HashMap<u64, String>> {
   )
 }
 
-async fn fetch_games() {
-  let Ok(value) = reqwest::get("https://api.steampowered.com/ISteamApps/GetAppList/v2")
-    .and_then(|response| response.json::<Value>())
-    .await
-  else {
+#[derive(Default, Serialize, Deserialize)]
+struct AppListCache {
+  synced_at: Option<i64>,
+  names: HashMap<u64, String>,
+}
+
+fn app_list_path(cache_directory: &Path) -> PathBuf {
+  cache_directory.join("steam_app_list.json")
+}
+
+fn load_app_list(cache_directory: &Path) {
+  let Ok(contents) = std::fs::read_to_string(app_list_path(cache_directory)) else {
     return;
   };
 
-  if let Some(list) = convert_game_list(value) {
-    *GAME_NAMES.write().unwrap() = list;
+  match serde_json::from_str::<AppListCache>(&contents) {
+    Ok(cache) => {
+      GAME_NAMES.write().unwrap().extend(cache.names);
+      *APP_LIST_SYNCED_AT.write().unwrap() = cache.synced_at;
+    }
+    Err(error) => warn!("ignoring unreadable steam app list cache: {error}"),
   }
 }
 
-pub async fn run(config: &Config) {
+#[derive(Deserialize)]
+struct AppListBase {
+  response: AppListResponse,
+}
+
+#[derive(Deserialize)]
+struct AppListResponse {
+  #[serde(default)]
+  apps: Vec<AppListEntry>,
+  #[serde(default)]
+  have_more_results: bool,
+  last_appid: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct AppListEntry {
+  appid: u64,
+  name: String,
+}
+
+/// pulls every app changed since the last sync, a page at a time, and saves the result to disk
+async fn sync_app_list(api_key: &str) {
+  let started_at = Utc::now().timestamp();
+  let modified_since = APP_LIST_SYNCED_AT.read().unwrap().unwrap_or_default();
+  let mut last_appid = 0;
+  let mut updated = 0;
+
+  loop {
+    let result = reqwest::get(format!(
+      "https://api.steampowered.com/IStoreService/GetAppList/v1/?key={api_key}&if_modified_since={modified_since}&last_appid={last_appid}&max_results=50000&include_games=true&include_software=true"
+    ))
+    .and_then(|response| response.json::<AppListBase>())
+    .await
+    // the api key is part of the url, which errors would otherwise print
+    .map_err(reqwest::Error::without_url);
+
+    let response = match result {
+      Ok(base) => base.response,
+      Err(error) => {
+        // leave the sync time alone so the next attempt picks up where this one should have
+        tracing::error!("failed to sync steam app list: {error:?}");
+        return;
+      }
+    };
+
+    updated += response.apps.len();
+    GAME_NAMES
+      .write()
+      .unwrap()
+      .extend(response.apps.into_iter().map(|app| (app.appid, app.name)));
+
+    match response.last_appid {
+      Some(appid) if response.have_more_results => last_appid = appid,
+      _ => break,
+    }
+  }
+
+  *APP_LIST_SYNCED_AT.write().unwrap() = Some(started_at);
+  tracing::info!("synced {updated} steam apps");
+  save_cache(CacheFile::AppList);
+}
+
+#[derive(Deserialize)]
+struct SummariesBase {
+  response: Summaries,
+}
+
+#[derive(Deserialize)]
+struct Summaries {
+  players: Vec<PlayerSummary>,
+}
+
+#[derive(Deserialize)]
+struct PlayerSummary {
+  #[serde(rename = "steamid", deserialize_with = "number_from_string")]
+  steam_id: u64,
+  #[serde(rename = "personaname")]
+  persona_name: String,
+  #[serde(rename = "personastate")]
+  persona_state: SteamPersonaState,
+  #[serde(rename = "lastlogoff")]
+  last_logoff: Option<i64>,
+  avatar: String,
+  #[serde(rename = "avatarmedium")]
+  avatar_medium: String,
+  #[serde(rename = "avatarfull")]
+  avatar_full: String,
+  #[serde(rename = "profileurl")]
+  profile_url: String,
+  #[serde(rename = "gameid")]
+  game_id: Option<String>,
+  #[serde(rename = "gameextrainfo")]
+  game_extra_info: Option<String>,
+}
+
+fn number_from_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+  String::deserialize(deserializer)?
+    .parse()
+    .map_err(serde::de::Error::custom)
+}
+
+async fn fetch_player_summaries(
+  api_key: &str,
+  players: Vec<SteamId>,
+) -> reqwest::Result<Vec<PlayerSummary>> {
+  let mut summaries = Vec::with_capacity(players.len());
+
+  // steam only takes 100 ids per request
+  for players in players.chunks(100) {
+    let steam_ids = players
+      .iter()
+      .map(|steam_id| steam_id.into_u64().to_string())
+      .collect::<Vec<_>>()
+      .join(",");
+
+    let response = reqwest::get(format!(
+      "https://api.steampowered.com/ISteamUser/GetPlayerSummaries/v2/?key={api_key}&steamids={steam_ids}"
+    ))
+    .and_then(|response| response.json::<SummariesBase>())
+    .await
+    // the api key is part of the url, which errors would otherwise print
+    .map_err(reqwest::Error::without_url)?;
+    summaries.extend(response.response.players);
+  }
+
+  Ok(summaries)
+}
+
+#[derive(Deserialize)]
+struct GamesBase {
+  response: GamesResponse,
+}
+
+/// private profiles come back with an empty response
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct GamesResponse {
+  game_count: Option<u64>,
+  games: Vec<PlayedGame>,
+}
+
+#[derive(Deserialize)]
+struct PlayedGame {
+  appid: u64,
+  name: Option<String>,
+  playtime_2weeks: Option<u64>,
+  playtime_forever: u64,
+  img_icon_url: Option<String>,
+}
+
+impl From<PlayedGame> for SteamPlayedGame {
+  fn from(game: PlayedGame) -> Self {
+    let appid = game.appid;
+    SteamPlayedGame {
+      appid,
+      name: game
+        .name
+        .or_else(|| GAME_NAMES.read().unwrap().get(&appid).cloned())
+        .unwrap_or_else(|| "unknown game".to_owned()),
+      playtime_two_weeks: game.playtime_2weeks,
+      playtime_forever: game.playtime_forever,
+      icon_url: game
+        .img_icon_url
+        .filter(|hash| !hash.is_empty())
+        .map(|hash| {
+          format!(
+            "https://media.steampowered.com/steamcommunity/public/images/apps/{appid}/{hash}.jpg"
+          )
+        }),
+      header_url: format!("https://cdn.cloudflare.steamstatic.com/steam/apps/{appid}/header.jpg"),
+    }
+  }
+}
+
+async fn fetch_player_games(
+  api_key: &str,
+  steam_id: SteamId,
+  method: &str,
+) -> Option<GamesResponse> {
+  let steam_id = steam_id.into_u64();
+  reqwest::get(format!(
+    "https://api.steampowered.com/IPlayerService/{method}/v1/?key={api_key}&steamid={steam_id}&include_appinfo=1&include_played_free_games=1&format=json"
+  ))
+  .and_then(|response| response.json::<GamesBase>())
+  .await
+  // the api key is part of the url, which errors would otherwise print
+  .map_err(reqwest::Error::without_url)
+  .inspect_err(|error| tracing::error!("failed to fetch {method} for {steam_id}: {error:?}"))
+  .map(|base| base.response)
+  .ok()
+}
+
+async fn update_game_stats(api_key: &str, steam_id: SteamId) {
+  let (recent, owned) = futures::join!(
+    fetch_player_games(api_key, steam_id, "GetRecentlyPlayedGames"),
+    fetch_player_games(api_key, steam_id, "GetOwnedGames"),
+  );
+
+  let mut game_stats = GAME_STATS.write().unwrap();
+  let stats = game_stats.entry(steam_id.into_u64()).or_default();
+
+  if let Some(recent) = recent {
+    stats.recently_played = recent.games.into_iter().map(Into::into).collect();
+  }
+
+  if let Some(mut owned) = owned {
+    owned
+      .games
+      .sort_by_key(|game| std::cmp::Reverse(game.playtime_forever));
+
+    stats.library = Some(SteamLibrary {
+      game_count: owned.game_count.unwrap_or(owned.games.len() as u64),
+      total_playtime: owned.games.iter().map(|game| game.playtime_forever).sum(),
+      most_played: owned
+        .games
+        .into_iter()
+        .take(MOST_PLAYED_COUNT)
+        .map(Into::into)
+        .collect(),
+    });
+  }
+}
+
+pub async fn run(config: &'static Config) {
   let Some(steam_api_key) = &config.steam_api_key else {
     return;
   };
@@ -76,66 +829,153 @@ pub async fn run(config: &Config) {
   let players = config
     .users
     .values()
-    .filter_map(|config| config.steam_id)
+    .filter_map(|user| {
+      let interval = config.intervals.steam_for(&user.intervals);
+      Some((user.steam_id?, interval))
+    })
     .collect::<Vec<_>>();
 
-  let steam = Steam::new(&steam_api_key);
+  if players.is_empty() {
+    return;
+  }
+
+  load_app_details(&config.cache_directory);
+  load_app_list(&config.cache_directory);
+  start_cache_writer(&config.cache_directory);
 
-  async fn perform_update(steam: &Steam, players: &Vec<SteamId>) {
-    match steam.get_player_summaries(players.clone()).await {
+  /// returns the players who are currently in a game, if we could find out
+  async fn perform_update(api_key: &'static str, players: Vec<SteamId>) -> Option<HashSet<u64>> {
+    match fetch_player_summaries(api_key, players).await {
       Ok(players) => {
         let mut user_info = USER_INFO.write().unwrap();
         let game_names = GAME_NAMES.read().unwrap();
+        let mut active = HashSet::new();
         for player in players {
-          let game_id = player
-            .game_id
-            .map(|id| u64::from_str_radix(&id, 10).unwrap());
+          let game_id = player.game_id.and_then(|id| id.parse::<u64>().ok());
 
           let game_name = game_id.map(|id| SteamGameInfo {
             appid: id,
-            name: game_names
-              .get(&id)
-              .cloned()
+            // non-steam games only have a name in the extra info
+            name: player
+              .game_extra_info
+              .or_else(|| game_names.get(&id).cloned())
               .unwrap_or_else(|| "unknown game".to_owned()),
             info_url: format!(
               "http://store.steampowered.com/api/appdetails?appids={id}&filters=basic"
             ),
+            details: None,
+            achievements: None,
           });
 
+          if let Some(id) = game_id {
+            request_app_details(id);
+            let steam_id = player.steam_id;
+            if PENDING_ACHIEVEMENTS.lock().unwrap().insert(steam_id) {
+              tokio::spawn(async move {
+                update_achievements(api_key, steam_id, id).await;
+                PENDING_ACHIEVEMENTS.lock().unwrap().remove(&steam_id);
+              });
+            }
+          }
+
+          if game_name.is_some() {
+            active.insert(player.steam_id);
+          }
+
           user_info.insert(
-            player.steam_id.into_u64(),
+            player.steam_id,
             SteamUserInfo {
-              steam_id: player.steam_id.into_u64().to_string(),
+              steam_id: player.steam_id.to_string(),
               persona_name: player.persona_name,
+              persona_state: player.persona_state,
+              last_logoff: player
+                .last_logoff
+                .and_then(|last_logoff| DateTime::from_timestamp(last_logoff, 0)),
+              avatar: SteamAvatar {
+                small: player.avatar,
+                medium: player.avatar_medium,
+                full: player.avatar_full,
+              },
+              profile_url: player.profile_url,
               game: game_name,
+              recently_played: Vec::new(),
+              library: None,
             },
           );
         }
+
+        Some(active)
       }
       Err(error) => {
         tracing::error!("failed to fetch player summaries: {error:?}");
+        None
       }
-    };
+    }
   }
 
   tokio::spawn(async move {
-    let mut interval = tokio::time::interval(Duration::from_secs(21600));
-
-    // preventing an easy rate limit in case we repeatedly restart
-    interval.tick().await;
+    let period = Duration::from_secs(config.intervals.steam_app_list);
+    // a recent sync from before a restart still counts, so restarting doesn't mean refetching
+    let since_sync = APP_LIST_SYNCED_AT
+      .read()
+      .unwrap()
+      .map(|synced_at| Duration::from_secs((Utc::now().timestamp() - synced_at).max(0) as u64));
+    let first_sync =
+      Instant::now() + since_sync.map_or(Duration::ZERO, |since| period.saturating_sub(since));
+    let mut interval = tokio::time::interval_at(first_sync, period);
 
     loop {
       interval.tick().await;
-      fetch_games().await;
+      sync_app_list(steam_api_key).await;
     }
   });
 
+  let stats_players = players
+    .iter()
+    .map(|(steam_id, _)| *steam_id)
+    .collect::<Vec<_>>();
   tokio::spawn(async move {
-    let mut interval = tokio::time::interval(Duration::from_secs(30));
+    let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.steam_games));
 
     loop {
       interval.tick().await;
-      perform_update(&steam, &players).await;
+      for steam_id in &stats_players {
+        update_game_stats(steam_api_key, *steam_id).await;
+      }
+    }
+  });
+
+  tokio::spawn(async move {
+    let mut schedule = players
+      .into_iter()
+      .map(|(steam_id, interval)| (steam_id, interval, Instant::now()))
+      .collect::<Vec<(SteamId, PollInterval, Instant)>>();
+
+    loop {
+      let next_poll = schedule
+        .iter()
+        .map(|(_, _, next_poll)| *next_poll)
+        .min()
+        .expect("there is at least one player");
+      tokio::time::sleep_until(next_poll).await;
+
+      // everyone who's due gets batched into a single request
+      let now = Instant::now();
+      let due = schedule
+        .iter()
+        .filter(|(_, _, next_poll)| *next_poll <= now)
+        .map(|(steam_id, _, _)| *steam_id)
+        .collect::<Vec<_>>();
+
+      let active = perform_update(steam_api_key, due).await;
+      for (steam_id, interval, next_poll) in &mut schedule {
+        if *next_poll <= now {
+          let is_active = active
+            .as_ref()
+            .is_some_and(|active| active.contains(&steam_id.into_u64()));
+          *next_poll = Instant::now() + interval.get(is_active);
+        }
+      }
     }
   });
 
@@ -0,0 +1,237 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+  time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use ts_rs::TS;
+
+use crate::config::Config;
+
+/// helix only takes this many logins per request
+const LOGINS_PER_REQUEST: usize = 100;
+
+#[derive(Clone, Serialize, TS)]
+pub struct TwitchUserInfo {
+  login: String,
+  live: bool,
+  stream: Option<TwitchStream>,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct TwitchStream {
+  title: String,
+  game: String,
+  viewers: u64,
+  started_at: DateTime<Utc>,
+  thumbnail_url: String,
+}
+
+impl TwitchUserInfo {
+  pub fn activity(&self) -> Option<String> {
+    let stream = self.stream.as_ref()?;
+    Some(match stream.game.as_str() {
+      "" => "streaming on twitch".to_owned(),
+      game => format!("streaming {game} on twitch"),
+    })
+  }
+}
+
+static USERS: LazyLock<RwLock<HashMap<String, TwitchUserInfo>>> = LazyLock::new(Default::default);
+
+pub fn fetch_twitch_info(login: &str) -> Option<TwitchUserInfo> {
+  USERS.read().unwrap().get(&login.to_lowercase()).cloned()
+}
+
+pub fn run(config: &'static Config) {
+  let (Some(client_id), Some(client_secret)) =
+    (&config.twitch_client_id, &config.twitch_client_secret)
+  else {
+    return;
+  };
+
+  // twitch logins are always lowercase in responses
+  let logins = config
+    .users
+    .values()
+    .filter_map(|user| user.twitch_login.as_ref())
+    .map(|login| login.to_lowercase())
+    .collect::<Vec<_>>();
+  if logins.is_empty() {
+    return;
+  }
+
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.twitch));
+    let mut token: Option<AppToken> = None;
+
+    loop {
+      interval.tick().await;
+
+      if token
+        .as_ref()
+        .is_none_or(|token| token.expires_at <= Instant::now())
+      {
+        token = request_token(config, client_id, client_secret).await;
+      }
+      let Some(access_token) = token.as_ref().map(|token| token.access_token.as_str()) else {
+        continue;
+      };
+
+      let mut streams = Vec::new();
+      let mut failed = false;
+      for chunk in logins.chunks(LOGINS_PER_REQUEST) {
+        match request_streams(config, client_id, access_token, chunk).await {
+          Ok(chunk_streams) => streams.extend(chunk_streams),
+          Err(StreamsError::Unauthorized) => {
+            tracing::warn!("twitch app token was rejected, requesting a new one");
+            token = None;
+            failed = true;
+            break;
+          }
+          Err(StreamsError::Request(error)) => {
+            tracing::error!("failed to request twitch streams: {error}");
+            failed = true;
+            break;
+          }
+        }
+      }
+      // a partial answer would mark everyone in the missing chunks as offline
+      if failed {
+        continue;
+      }
+
+      let mut streams = streams
+        .into_iter()
+        .filter(|stream| stream.kind == "live")
+        .map(|stream| (stream.user_login.clone(), stream))
+        .collect::<HashMap<_, _>>();
+
+      *USERS.write().unwrap() = logins
+        .iter()
+        .map(|login| {
+          let stream = streams.remove(login).map(|stream| TwitchStream {
+            title: stream.title,
+            game: stream.game_name,
+            viewers: stream.viewer_count,
+            started_at: stream.started_at,
+            thumbnail_url: stream
+              .thumbnail_url
+              .replace("{width}", "1280")
+              .replace("{height}", "720"),
+          });
+
+          (
+            login.clone(),
+            TwitchUserInfo {
+              login: login.clone(),
+              live: stream.is_some(),
+              stream,
+            },
+          )
+        })
+        .collect();
+    }
+  });
+
+  tracing::info!("started twitch fetcher");
+}
+
+struct AppToken {
+  access_token: String,
+  expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct RawToken {
+  access_token: String,
+  expires_in: u64,
+}
+
+async fn request_token(config: &Config, client_id: &str, client_secret: &str) -> Option<AppToken> {
+  let requested_at = Instant::now();
+  let token = reqwest::Client::new()
+    .post(&config.twitch_auth_url)
+    .form(&[
+      ("client_id", client_id),
+      ("client_secret", client_secret),
+      ("grant_type", "client_credentials"),
+    ])
+    .send()
+    .await
+    .and_then(|response| response.error_for_status())
+    .inspect_err(|error| tracing::error!("failed to request twitch app token: {error}"))
+    .ok()?
+    .json::<RawToken>()
+    .await
+    .inspect_err(|error| tracing::error!("failed to parse twitch app token: {error}"))
+    .ok()?;
+
+  Some(AppToken {
+    access_token: token.access_token,
+    // refresh a little early so a poll never races the expiry
+    expires_at: requested_at + Duration::from_secs(token.expires_in.saturating_sub(60)),
+  })
+}
+
+#[derive(Deserialize)]
+struct StreamsBase {
+  data: Vec<RawStream>,
+}
+
+#[derive(Deserialize)]
+struct RawStream {
+  user_login: String,
+  #[serde(rename = "type")]
+  kind: String,
+  title: String,
+  game_name: String,
+  viewer_count: u64,
+  started_at: DateTime<Utc>,
+  thumbnail_url: String,
+}
+
+enum StreamsError {
+  Unauthorized,
+  Request(reqwest::Error),
+}
+
+async fn request_streams(
+  config: &Config,
+  client_id: &str,
+  access_token: &str,
+  logins: &[String],
+) -> Result<Vec<RawStream>, StreamsError> {
+  // helix only returns 20 streams unless asked for more, which would hide anyone past that
+  let first = LOGINS_PER_REQUEST.to_string();
+  let query = logins
+    .iter()
+    .map(|login| ("user_login", login.as_str()))
+    .chain([("first", first.as_str())])
+    .collect::<Vec<_>>();
+
+  let response = reqwest::Client::new()
+    .get(format!("{}/streams", config.twitch_api_url))
+    .query(&query)
+    .header("Client-Id", client_id)
+    .bearer_auth(access_token)
+    .send()
+    .await
+    .map_err(StreamsError::Request)?;
+
+  if response.status() == StatusCode::UNAUTHORIZED {
+    return Err(StreamsError::Unauthorized);
+  }
+
+  response
+    .error_for_status()
+    .map_err(StreamsError::Request)?
+    .json::<StreamsBase>()
+    .await
+    .map(|base| base.data)
+    .map_err(StreamsError::Request)
+}
@@ -0,0 +1,182 @@
+use std::{
+  collections::HashMap,
+  sync::{LazyLock, RwLock},
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::TryFutureExt;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use ts_rs::TS;
+
+use crate::config::{Config, WakaTimeConfig, has_scope};
+
+/// wakatime stops counting time after this long without a heartbeat, so we stop calling it coding too
+const IDLE_AFTER: TimeDelta = TimeDelta::minutes(15);
+
+#[derive(Clone, Serialize, TS)]
+pub struct WakaTimeUserInfo {
+  /// only set while the user has sent a heartbeat recently
+  coding: Option<CodingStatus>,
+  today_seconds: u64,
+  today_text: String,
+}
+
+#[derive(Clone, Serialize, TS)]
+pub struct CodingStatus {
+  editor: Option<String>,
+  language: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  project: Option<String>,
+  last_heartbeat: DateTime<Utc>,
+}
+
+impl WakaTimeUserInfo {
+  pub fn activity(&self) -> Option<String> {
+    let coding = self.coding.as_ref()?;
+    Some(match (&coding.language, &coding.project) {
+      (Some(language), Some(project)) => format!("coding in {language} on {project}"),
+      (Some(language), None) => format!("coding in {language}"),
+      (None, Some(project)) => format!("coding on {project}"),
+      (None, None) => "coding".to_owned(),
+    })
+  }
+}
+
+static USERS: LazyLock<RwLock<HashMap<String, WakaTimeUserInfo>>> = LazyLock::new(Default::default);
+
+pub fn fetch_wakatime_info(username: &str, auth_scopes: &[String]) -> Option<WakaTimeUserInfo> {
+  let mut info = USERS.read().unwrap().get(username).cloned()?;
+  if let Some(coding) = &mut info.coding {
+    if !has_scope(auth_scopes, "wakatime.project") {
+      coding.project = None;
+    }
+    if Utc::now() - coding.last_heartbeat > IDLE_AFTER {
+      info.coding = None;
+    }
+  }
+
+  Some(info)
+}
+
+pub fn run(config: &'static Config) {
+  let mut started = false;
+  for (username, user) in &config.users {
+    let Some(wakatime) = &user.wakatime else {
+      continue;
+    };
+    let interval = config.intervals.wakatime_for(&user.intervals);
+
+    started = true;
+    tokio::spawn(async move {
+      loop {
+        let coding = update_user(username, wakatime).await;
+        tokio::time::sleep(interval.get(coding.unwrap_or_default())).await;
+      }
+    });
+  }
+
+  if started {
+    tracing::info!("started wakatime fetcher");
+  }
+}
+
+#[derive(Deserialize)]
+struct DataBase<T> {
+  data: T,
+}
+
+#[derive(Deserialize)]
+struct Summary {
+  grand_total: GrandTotal,
+  #[serde(default)]
+  editors: Vec<SummaryItem>,
+  range: SummaryRange,
+}
+
+#[derive(Deserialize)]
+struct GrandTotal {
+  total_seconds: f64,
+  text: String,
+}
+
+#[derive(Deserialize)]
+struct SummaryItem {
+  name: String,
+}
+
+#[derive(Deserialize)]
+struct SummaryRange {
+  date: String,
+}
+
+#[derive(Deserialize)]
+struct Heartbeat {
+  /// unix time, with fractional seconds
+  time: f64,
+  project: Option<String>,
+  language: Option<String>,
+  /// only wakapi reports the editor on heartbeats
+  editor: Option<String>,
+}
+
+async fn request<T: DeserializeOwned>(wakatime: &WakaTimeConfig, path: &str) -> reqwest::Result<T> {
+  reqwest::Client::new()
+    .get(format!("{}/users/current/{path}", wakatime.base_url))
+    .query(&[("api_key", &wakatime.api_key)])
+    .send()
+    .and_then(|response| async move { response.error_for_status() })
+    .and_then(|response| response.json::<DataBase<T>>())
+    .await
+    .map(|base| base.data)
+    // the api key is part of the url, which errors would otherwise print
+    .map_err(reqwest::Error::without_url)
+}
+
+/// returns whether the user is currently coding, if we could find out
+async fn update_user(username: &str, wakatime: &WakaTimeConfig) -> Option<bool> {
+  let summary = match request::<Vec<Summary>>(wakatime, "summaries?range=today").await {
+    Ok(summaries) => summaries.into_iter().next()?,
+    Err(error) => {
+      tracing::error!("failed to request wakatime summary for user {username}: {error}");
+      return None;
+    }
+  };
+
+  // the summary's date is in the user's own time zone, which is what heartbeats are grouped by
+  let heartbeat =
+    request::<Vec<Heartbeat>>(wakatime, &format!("heartbeats?date={}", summary.range.date))
+      .await
+      .inspect_err(|error| {
+        tracing::error!("failed to request wakatime heartbeats for user {username}: {error}")
+      })
+      .ok()
+      .and_then(|heartbeats| {
+        heartbeats
+          .into_iter()
+          .max_by(|a, b| a.time.total_cmp(&b.time))
+      });
+
+  let coding = heartbeat.and_then(|heartbeat| {
+    let last_heartbeat = DateTime::from_timestamp(heartbeat.time as i64, 0)?;
+    (Utc::now() - last_heartbeat <= IDLE_AFTER).then(|| CodingStatus {
+      editor: heartbeat
+        .editor
+        .or_else(|| summary.editors.first().map(|editor| editor.name.clone())),
+      language: heartbeat.language,
+      project: heartbeat.project,
+      last_heartbeat,
+    })
+  });
+  let is_coding = coding.is_some();
+
+  USERS.write().unwrap().insert(
+    username.to_owned(),
+    WakaTimeUserInfo {
+      coding,
+      today_seconds: summary.grand_total.total_seconds as u64,
+      today_text: summary.grand_total.text,
+    },
+  );
+
+  Some(is_coding)
+}
@@ -28,9 +28,6 @@ impl HandlerConfig {
       })
       .collect();
 
-    HandlerConfig {
-      config: config,
-      domains,
-    }
+    HandlerConfig { config, domains }
   }
 }
@@ -1,15 +1,25 @@
+mod activity;
 mod config;
 mod fetchers;
 mod host_config;
 mod middleware;
 mod routes;
+mod user;
+mod webhooks;
 
 use std::fs::read_to_string;
 
-use axum::{Router, ServiceExt, handler::Handler, middleware as mw, routing::get};
+use axum::{
+  Router, ServiceExt,
+  handler::Handler,
+  middleware as mw,
+  routing::{get, post, put},
+};
 use host_config::HandlerConfig;
 use routes::{
-  get_host_user::get_host_user, get_user::get_user, get_users::get_users, root::root_page,
+  get_host_user::get_host_user, get_user::get_user, get_users::get_users,
+  get_webhook_deliveries::get_webhook_deliveries, health::get_health,
+  post_owntracks::post_owntracks, put_status::put_status, root::root_page,
 };
 use tower::Layer;
 
@@ -22,12 +32,22 @@ async fn main() -> anyhow::Result<()> {
   let config = read_to_string(config_arg).expect("failed to read config");
   let config = &*Box::leak(toml::from_str(&config).expect("failed to parse config"));
 
-  fetchers::discord::run_discord_bot(&config).await?;
-  fetchers::last_fm::run(&config).await;
-  fetchers::steam::run(&config).await;
-  fetchers::icloud::run(&config);
+  fetchers::discord::run_discord_bot(config).await?;
+  fetchers::last_fm::run(config).await;
+  fetchers::listenbrainz::run(config);
+  fetchers::steam::run(config).await;
+  fetchers::icloud::run(config);
+  fetchers::github::run(config);
+  fetchers::wakatime::run(config);
+  fetchers::media_server::run(config);
+  fetchers::fediverse::run(config);
+  fetchers::bluesky::run(config);
+  fetchers::twitch::run(config);
+  fetchers::home_assistant::run(config);
+  fetchers::json_source::run(config);
+  webhooks::run(config);
 
-  let handler_config = &*Box::leak(Box::new(HandlerConfig::new(&config)));
+  let handler_config = &*Box::leak(Box::new(HandlerConfig::new(config)));
   let middleware = mw::from_fn_with_state(handler_config, middleware::host_rerouter);
 
   let app = Router::new()
@@ -47,6 +67,13 @@ async fn main() -> anyhow::Result<()> {
       "/user/{user}",
       get(get_user.layer(mw::from_fn_with_state(10, middleware::age_caching))),
     )
+    .route("/user/{user}/status/{source}", put(put_status))
+    .route("/user/{user}/owntracks", post(post_owntracks))
+    .route("/webhooks/deliveries", get(get_webhook_deliveries))
+    .route(
+      "/health",
+      get(get_health.layer(mw::from_fn_with_state(5, middleware::age_caching))),
+    )
     .layer(mw::from_fn(middleware::cors))
     .with_state(handler_config);
 
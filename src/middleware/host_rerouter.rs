@@ -51,7 +51,7 @@ pub async fn host_rerouter(
     replace_with(
       request.uri_mut(),
       || Uri::from_static("https://docs.rs/ahv/latest/ahv"),
-      |uri| rewrite_uri(uri),
+      rewrite_uri,
     );
   }
 
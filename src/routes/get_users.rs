@@ -25,5 +25,5 @@ pub async fn get_users(
 ) -> Json<&'static Value> {
   static USERS_RESPONSE: OnceLock<Value> = OnceLock::new();
 
-  axum::Json(USERS_RESPONSE.get_or_init(|| create_users_response(&handler_config.config)))
+  axum::Json(USERS_RESPONSE.get_or_init(|| create_users_response(handler_config.config)))
 }
@@ -0,0 +1,28 @@
+use axum::{
+  Json,
+  extract::State,
+  http::StatusCode,
+  response::{IntoResponse, Response},
+};
+use axum_extra::{
+  TypedHeader,
+  headers::{Authorization, authorization::Bearer},
+};
+
+use crate::{
+  config::{has_scope, scopes_from_bearer},
+  host_config::HandlerConfig,
+  webhooks,
+};
+
+pub async fn get_webhook_deliveries(
+  State(handler_config): State<&'static HandlerConfig>,
+  bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Response {
+  let auth_scopes = scopes_from_bearer(bearer, handler_config.config);
+  if !has_scope(&auth_scopes, "webhooks") {
+    return StatusCode::UNAUTHORIZED.into_response();
+  }
+
+  Json(webhooks::fetch_deliveries()).into_response()
+}
@@ -0,0 +1,29 @@
+use axum::{Json, extract::State};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{
+  fetchers::{discord, last_fm},
+  host_config::HandlerConfig,
+};
+
+#[derive(Serialize, TS)]
+#[ts(export, rename = "Health")]
+pub struct HealthResponse {
+  discord: Option<discord::GatewayHealth>,
+  last_fm: Option<last_fm::Health>,
+}
+
+pub async fn get_health(
+  State(handler_config): State<&'static HandlerConfig>,
+) -> Json<HealthResponse> {
+  let config = handler_config.config;
+
+  Json(HealthResponse {
+    discord: config
+      .discord_bot_token
+      .is_some()
+      .then(discord::fetch_health),
+    last_fm: config.last_fm_key.is_some().then(last_fm::fetch_health),
+  })
+}
@@ -6,6 +6,10 @@ use crate::config::Config;
 pub mod get_host_user;
 pub mod get_user;
 pub mod get_users;
+pub mod get_webhook_deliveries;
+pub mod health;
+pub mod post_owntracks;
+pub mod put_status;
 pub mod root;
 
 #[derive(Serialize, TS)]
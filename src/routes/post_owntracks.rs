@@ -0,0 +1,68 @@
+use axum::{
+  Json,
+  body::Bytes,
+  extract::{Path, State},
+  http::StatusCode,
+  response::{IntoResponse, Response},
+};
+use axum_extra::{
+  TypedHeader,
+  headers::{Authorization, authorization::Basic},
+};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+
+use crate::{
+  fetchers::owntracks::{self, Message},
+  host_config::HandlerConfig,
+};
+
+pub async fn post_owntracks(
+  State(handler_config): State<&'static HandlerConfig>,
+  Path(user): Path<String>,
+  basic: Option<TypedHeader<Authorization<Basic>>>,
+  body: Bytes,
+) -> Response {
+  let Some(owntracks) = handler_config
+    .config
+    .users
+    .get(&user)
+    .and_then(|user| user.owntracks.as_ref())
+  else {
+    return StatusCode::NOT_FOUND.into_response();
+  };
+
+  // the apps send the username and password from their http settings as basic auth
+  let authorized = basic.is_some_and(|TypedHeader(Authorization(basic))| {
+    basic.username() == user && password_matches(basic.password(), &owntracks.password)
+  });
+  if !authorized {
+    return StatusCode::UNAUTHORIZED.into_response();
+  }
+
+  // only parsed once we know who sent it
+  let Ok(message) = serde_json::from_slice::<Message>(&body) else {
+    return StatusCode::BAD_REQUEST.into_response();
+  };
+
+  owntracks::handle_message(handler_config.config, &user, message).await;
+
+  // the apps expect a list of messages to hand back to the device, which we never have
+  Json(json!([])).into_response()
+}
+
+/// compares macs of both passwords with hmac's constant time check, so response times can't give
+/// away how much of a guess was right
+fn password_matches(given: &str, expected: &str) -> bool {
+  let mac = |password: &str| {
+    let mut mac =
+      Hmac::<Sha256>::new_from_slice(expected.as_bytes()).expect("hmac accepts keys of any length");
+    mac.update(password.as_bytes());
+    mac
+  };
+
+  mac(given)
+    .verify_slice(&mac(expected).finalize().into_bytes())
+    .is_ok()
+}
@@ -0,0 +1,51 @@
+use axum::{
+  Json,
+  extract::{Path, State},
+  http::StatusCode,
+};
+use axum_extra::{
+  TypedHeader,
+  headers::{Authorization, authorization::Bearer},
+};
+
+use crate::{
+  fetchers::custom::{self, PushedStatus},
+  host_config::HandlerConfig,
+};
+
+fn valid_source(source: &str) -> bool {
+  !source.is_empty()
+    && source.len() <= 64
+    && source
+      .bytes()
+      .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+}
+
+pub async fn put_status(
+  State(handler_config): State<&'static HandlerConfig>,
+  Path((user, source)): Path<(String, String)>,
+  bearer: Option<TypedHeader<Authorization<Bearer>>>,
+  Json(status): Json<PushedStatus>,
+) -> StatusCode {
+  if !handler_config.config.users.contains_key(&user) {
+    return StatusCode::NOT_FOUND;
+  }
+
+  let Some(auth) = bearer
+    .and_then(|TypedHeader(Authorization(bearer))| handler_config.config.auth.get(bearer.token()))
+  else {
+    return StatusCode::UNAUTHORIZED;
+  };
+
+  // tokens are only allowed to push status for the users they're scoped to
+  if !auth.scopes.contains(&format!("status.{user}")) {
+    return StatusCode::FORBIDDEN;
+  }
+
+  if !valid_source(&source) {
+    return StatusCode::BAD_REQUEST;
+  }
+
+  custom::push_status(&user, &source, status);
+  StatusCode::NO_CONTENT
+}
@@ -8,7 +8,11 @@ pub async fn root_page() -> Json<Value> {
       "/": "root page",
       "/users": "a summary of all the available users",
       "/user": "the information about a specific user, if the site is being accessed from a user's domain",
-      "/user/<username>": "the information about a specific user"
+      "/user/<username>": "the information about a specific user",
+      "/user/<username>/status/<source>": "PUT a custom status for a user, for tokens with the status.<username> scope",
+      "/user/<username>/owntracks": "POST locations from the owntracks apps in http mode, with the user's owntracks password",
+      "/health": "the connection status of the fetchers",
+      "/webhooks/deliveries": "recent webhook delivery attempts, for tokens with the webhooks scope"
     }
   }))
 }
@@ -0,0 +1,181 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use serde::Serialize;
+use serde_json::Value;
+use ts_rs::TS;
+
+use crate::{
+  activity::{self, ActivityKind},
+  config::UserConfig,
+  fetchers::{
+    bluesky, custom, discord, fediverse, github, home_assistant, icloud, json_source, last_fm,
+    listenbrainz, media_server, owntracks, steam, twitch, wakatime,
+  },
+};
+
+#[derive(Serialize, TS)]
+#[ts(export, rename = "User")]
+pub struct UserAggregate<'a> {
+  name: &'a str,
+  aliases: &'a Vec<String>,
+  pronouns: &'a Vec<String>,
+  time_zone: &'a str,
+  discord: Option<discord::DiscordUserInfo>,
+  last_fm: Option<last_fm::UserInfo>,
+  listenbrainz: Option<last_fm::UserInfo>,
+  steam: Option<steam::SteamUserInfo>,
+  location: Option<icloud::Location>,
+  github: Option<github::GitHubUserInfo>,
+  wakatime: Option<wakatime::WakaTimeUserInfo>,
+  watching: Option<media_server::MediaSession>,
+  fediverse: Option<fediverse::FediverseUserInfo>,
+  bluesky: Option<bluesky::BlueskyUserInfo>,
+  twitch: Option<twitch::TwitchUserInfo>,
+  home_assistant: Option<home_assistant::HomeAssistantUserInfo>,
+  /// pushed by the user's own scripts, keyed by source
+  custom: HashMap<String, custom::CustomStatus>,
+  /// from the json sources in the config, keyed by source and then field
+  sources: HashMap<String, HashMap<String, Value>>,
+  activity: activity::Summary,
+}
+
+impl UserAggregate<'_> {
+  pub fn activity(&self) -> &activity::Summary {
+    &self.activity
+  }
+
+  /// going by discord, the only source with a real online status
+  pub fn online(&self) -> Option<bool> {
+    self
+      .discord
+      .as_ref()
+      .map(discord::DiscordUserInfo::is_online)
+  }
+}
+
+/// everything known about a user, as seen with the given scopes
+pub fn build_user<'a>(
+  username: &str,
+  user: &'a UserConfig,
+  auth_scopes: Cow<'static, [String]>,
+) -> UserAggregate<'a> {
+  let mut location = user
+    .icloud_device_id
+    .as_deref()
+    .and_then(|id| icloud::get_user_info(id, auth_scopes.clone()))
+    .or_else(|| owntracks::get_location(username, &auth_scopes))
+    .or_else(|| home_assistant::get_location(username, &auth_scopes));
+
+  let mut aggregate = UserAggregate {
+    name: &user.name,
+    aliases: &user.aliases,
+    pronouns: &user.pronouns,
+    time_zone: location
+      .as_mut()
+      .and_then(|location| location.time_zone.take())
+      .unwrap_or(&user.time_zone),
+    discord: user
+      .discord_id
+      .and_then(|id| discord::fetch_user_info(id, &auth_scopes)),
+    last_fm: user
+      .last_fm_username
+      .as_deref()
+      .and_then(last_fm::fetch_lastfm_info),
+    listenbrainz: user
+      .listenbrainz
+      .as_ref()
+      .and_then(|listenbrainz| listenbrainz::fetch_listenbrainz_info(&listenbrainz.username)),
+    steam: user.steam_id.and_then(steam::get_user_info),
+    location,
+    github: user
+      .github
+      .as_ref()
+      .and_then(|github| github::fetch_github_info(&github.username)),
+    wakatime: wakatime::fetch_wakatime_info(username, &auth_scopes),
+    watching: media_server::get_user_info(username, &auth_scopes),
+    fediverse: user
+      .fediverse
+      .as_ref()
+      .and_then(|fediverse| fediverse::fetch_fediverse_info(&fediverse.handle)),
+    bluesky: user
+      .bluesky_handle
+      .as_deref()
+      .and_then(bluesky::fetch_bluesky_info),
+    twitch: user
+      .twitch_login
+      .as_deref()
+      .and_then(twitch::fetch_twitch_info),
+    home_assistant: home_assistant::fetch_home_assistant_info(username, &auth_scopes),
+    custom: custom::get_user_info(username),
+    sources: json_source::get_user_info(username),
+    activity: Default::default(),
+  };
+
+  aggregate.activity = activity::summarize(
+    &user.activity,
+    [
+      (
+        ActivityKind::Streaming,
+        aggregate
+          .twitch
+          .as_ref()
+          .and_then(twitch::TwitchUserInfo::activity),
+      ),
+      (
+        ActivityKind::Gaming,
+        aggregate
+          .steam
+          .as_ref()
+          .and_then(steam::SteamUserInfo::activity),
+      ),
+      (
+        ActivityKind::Watching,
+        aggregate
+          .watching
+          .as_ref()
+          .and_then(media_server::MediaSession::activity),
+      ),
+      (
+        ActivityKind::Voice,
+        aggregate
+          .discord
+          .as_ref()
+          .and_then(discord::DiscordUserInfo::voice_activity),
+      ),
+      (
+        ActivityKind::Listening,
+        aggregate
+          .last_fm
+          .as_ref()
+          .and_then(last_fm::UserInfo::activity),
+      ),
+      (
+        ActivityKind::Listening,
+        aggregate
+          .listenbrainz
+          .as_ref()
+          .and_then(last_fm::UserInfo::activity),
+      ),
+      (
+        ActivityKind::Coding,
+        aggregate
+          .wakatime
+          .as_ref()
+          .and_then(wakatime::WakaTimeUserInfo::activity),
+      ),
+      (
+        ActivityKind::Custom,
+        custom::latest_activity(&aggregate.custom),
+      ),
+      (
+        ActivityKind::Status,
+        aggregate
+          .discord
+          .as_ref()
+          .and_then(discord::DiscordUserInfo::status_activity),
+      ),
+    ],
+  );
+
+  aggregate
+}
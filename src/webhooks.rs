@@ -0,0 +1,326 @@
+use std::{
+  borrow::Cow,
+  collections::{HashMap, VecDeque},
+  sync::{
+    LazyLock, RwLock,
+    atomic::{AtomicU64, Ordering},
+  },
+  time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use ts_rs::TS;
+
+use crate::{
+  activity::ActivityKind,
+  config::{Config, WebhookConfig},
+  user::build_user,
+};
+
+const MAX_ATTEMPTS: u32 = 6;
+/// each retry after this waits three times as long as the one before
+const FIRST_RETRY: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const LOG_CAPACITY: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename = "WebhookEvent")]
+pub enum EventKind {
+  WentOnline,
+  WentOffline,
+  ActivityStarted,
+  ActivityChanged,
+  ActivityEnded,
+}
+
+/// the json body every subscriber gets
+#[derive(Serialize)]
+struct Event {
+  id: String,
+  event: EventKind,
+  user: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  activity: Option<ActivityKind>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  summary: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  previous_summary: Option<String>,
+  timestamp: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[ts(export, rename = "WebhookDelivery")]
+pub struct Delivery {
+  webhook: String,
+  event_id: String,
+  event: EventKind,
+  user: String,
+  attempt: u32,
+  attempted_at: DateTime<Utc>,
+  /// missing when the request never got a response
+  status: Option<u16>,
+  error: Option<String>,
+  will_retry: bool,
+}
+
+struct Pending {
+  name: &'static str,
+  webhook: &'static WebhookConfig,
+  event_id: String,
+  event: EventKind,
+  user: String,
+  /// serialized once so every attempt is signed over the same bytes
+  body: String,
+  attempt: u32,
+}
+
+/// what the last poll saw, to compare the next one against
+struct Snapshot {
+  online: Option<bool>,
+  activities: HashMap<ActivityKind, String>,
+}
+
+/// newest first
+static LOG: LazyLock<RwLock<VecDeque<Delivery>>> = LazyLock::new(Default::default);
+static NEXT_EVENT_ID: AtomicU64 = AtomicU64::new(0);
+
+pub fn fetch_deliveries() -> Vec<Delivery> {
+  LOG.read().unwrap().iter().cloned().collect()
+}
+
+pub fn run(config: &'static Config) {
+  if config.webhooks.is_empty() {
+    return;
+  }
+
+  let (sender, receiver) = unbounded_channel();
+  tokio::spawn(deliver(receiver, sender.clone()));
+
+  // webhooks with the same scopes see users the same way, so each set is only looked at once
+  let mut scope_sets = Vec::<&'static [String]>::new();
+  for webhook in config.webhooks.values() {
+    if !scope_sets.contains(&webhook.scopes.as_slice()) {
+      scope_sets.push(&webhook.scopes);
+    }
+  }
+
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.intervals.webhooks));
+    let mut previous = HashMap::<(&[String], &str), Snapshot>::new();
+
+    loop {
+      interval.tick().await;
+
+      for scopes in &scope_sets {
+        for (username, user) in &config.users {
+          let aggregate = build_user(username, user, Cow::Borrowed(*scopes));
+          let snapshot = Snapshot {
+            online: aggregate.online(),
+            activities: aggregate
+              .activity()
+              .signals()
+              .iter()
+              .map(|activity| (activity.kind(), activity.summary().to_owned()))
+              .collect(),
+          };
+
+          // the first poll is only a baseline, otherwise every restart would announce everything
+          let key = (*scopes, username.as_str());
+          if let Some(old) = previous.get(&key) {
+            for event in diff(username, old, &snapshot) {
+              enqueue(config, &sender, scopes, event);
+            }
+          }
+          previous.insert(key, snapshot);
+        }
+      }
+    }
+  });
+
+  tracing::info!("started webhooks");
+}
+
+fn diff(username: &str, old: &Snapshot, new: &Snapshot) -> Vec<Event> {
+  let event = |event, activity, summary, previous_summary| Event {
+    id: format!(
+      "{}-{}",
+      Utc::now().timestamp_millis(),
+      NEXT_EVENT_ID.fetch_add(1, Ordering::Relaxed)
+    ),
+    event,
+    user: username.to_owned(),
+    activity,
+    summary,
+    previous_summary,
+    timestamp: Utc::now(),
+  };
+
+  let mut events = Vec::new();
+  if let Some(online) = new.online.filter(|online| old.online != Some(*online)) {
+    let kind = match online {
+      true => EventKind::WentOnline,
+      false => EventKind::WentOffline,
+    };
+    events.push(event(kind, None, None, None));
+  }
+
+  for (kind, summary) in &new.activities {
+    match old.activities.get(kind) {
+      None => events.push(event(
+        EventKind::ActivityStarted,
+        Some(*kind),
+        Some(summary.clone()),
+        None,
+      )),
+      Some(previous) if previous != summary => events.push(event(
+        EventKind::ActivityChanged,
+        Some(*kind),
+        Some(summary.clone()),
+        Some(previous.clone()),
+      )),
+      Some(_) => {}
+    }
+  }
+
+  for (kind, previous) in &old.activities {
+    if !new.activities.contains_key(kind) {
+      events.push(event(
+        EventKind::ActivityEnded,
+        Some(*kind),
+        None,
+        Some(previous.clone()),
+      ));
+    }
+  }
+
+  events
+}
+
+fn matches(webhook: &WebhookConfig, event: &Event) -> bool {
+  (webhook.events.is_empty() || webhook.events.contains(&event.event))
+    && (webhook.users.is_empty() || webhook.users.contains(&event.user))
+    && event
+      .activity
+      .is_none_or(|kind| webhook.activities.is_empty() || webhook.activities.contains(&kind))
+}
+
+/// only goes to webhooks with the scopes the event was seen with
+fn enqueue(
+  config: &'static Config,
+  sender: &UnboundedSender<Pending>,
+  scopes: &[String],
+  event: Event,
+) {
+  let body = match serde_json::to_string(&event) {
+    Ok(body) => body,
+    Err(error) => {
+      tracing::error!("failed to serialize webhook event: {error}");
+      return;
+    }
+  };
+
+  for (name, webhook) in &config.webhooks {
+    if webhook.scopes != scopes || !matches(webhook, &event) {
+      continue;
+    }
+
+    // the receiver lives as long as the delivery task, which never stops
+    let _ = sender.send(Pending {
+      name,
+      webhook,
+      event_id: event.id.clone(),
+      event: event.event,
+      user: event.user.clone(),
+      body: body.clone(),
+      attempt: 0,
+    });
+  }
+}
+
+async fn deliver(mut receiver: UnboundedReceiver<Pending>, sender: UnboundedSender<Pending>) {
+  let client = reqwest::Client::new();
+
+  // each attempt gets its own task, so a slow subscriber can't hold up everyone else's events
+  while let Some(pending) = receiver.recv().await {
+    tokio::spawn(attempt(client.clone(), sender.clone(), pending));
+  }
+}
+
+async fn attempt(client: reqwest::Client, sender: UnboundedSender<Pending>, mut pending: Pending) {
+  pending.attempt += 1;
+
+  let (status, error, retryable) = match send(&client, &pending).await {
+    Ok(status) if status.is_success() => (Some(status), None, false),
+    // anything else in the 4xx range won't go any better the second time
+    Ok(status) => (
+      Some(status),
+      Some(format!("subscriber responded with {status}")),
+      status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS,
+    ),
+    Err(error) => (None, Some(error.to_string()), true),
+  };
+  let will_retry = retryable && pending.attempt < MAX_ATTEMPTS;
+
+  if let Some(error) = &error {
+    tracing::warn!(
+      "webhook {} failed to deliver event {} (attempt {}): {error}",
+      pending.name,
+      pending.event_id,
+      pending.attempt
+    );
+  }
+
+  {
+    let mut log = LOG.write().unwrap();
+    log.push_front(Delivery {
+      webhook: pending.name.to_owned(),
+      event_id: pending.event_id.clone(),
+      event: pending.event,
+      user: pending.user.clone(),
+      attempt: pending.attempt,
+      attempted_at: Utc::now(),
+      status: status.map(|status| status.as_u16()),
+      error,
+      will_retry,
+    });
+    log.truncate(LOG_CAPACITY);
+  }
+
+  // this task has nothing else to do, so it can wait out the delay itself
+  if will_retry {
+    tokio::time::sleep(FIRST_RETRY * 3u32.pow(pending.attempt - 1)).await;
+    let _ = sender.send(pending);
+  }
+}
+
+async fn send(client: &reqwest::Client, pending: &Pending) -> reqwest::Result<StatusCode> {
+  let mut mac = Hmac::<Sha256>::new_from_slice(pending.webhook.secret.as_bytes())
+    .expect("hmac accepts keys of any length");
+  mac.update(pending.body.as_bytes());
+  let signature = mac
+    .finalize()
+    .into_bytes()
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect::<String>();
+
+  let response = client
+    .post(&pending.webhook.url)
+    .header("Content-Type", "application/json")
+    .header("X-Webhook-Id", &pending.event_id)
+    .header("X-Signature-256", format!("sha256={signature}"))
+    .timeout(REQUEST_TIMEOUT)
+    .body(pending.body.clone())
+    .send()
+    .await?;
+
+  Ok(response.status())
+}